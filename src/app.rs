@@ -1,16 +1,59 @@
-use std::error;
+use std::{error, fs, path::{Path, PathBuf}, time::{Duration, Instant}};
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
-use crate::newsroomcore::newsroomstate::NewsroomState;
+use crate::newsroomcore::{newsroomstate::NewsroomState, newsarticle::NewsArticle};
 use crate::{newsroomcore::{
-    datasources::DataSources, newsfetchrss::fetch_articles, newsroomstate::NewsroomTransitions,
-}, settings::Settings};
+    articlestore::ArticleStore, datasources::DataSources, fetchpolicy::FetchPolicy, fetcherror::FetchError, fetchstatus::SourceStatus, httpcache::HttpCache,
+    fulltext::{FullTextCache, fetch_full_text}, discovery::{Discovery, discover}, digest::write_digest, formaction::FormAction,
+    newsfetchrss::fetch_articles, newsroomstate::NewsroomTransitions, readarticles::ReadArticles,
+    savedarticles::SavedArticles, sortmode::SortMode, listfilter::ListFilter,
+}, command::{self, CommandLine}, settings::{Settings, Theme, expand_home, opml_path}, tabs::{Form, FullText, Help, Reader, ThemePicker}};
 
-use log::info;
+use itertools::Itertools;
+use log::{info, warn};
 use tokio::sync::mpsc::{UnboundedSender, UnboundedReceiver, unbounded_channel};
-use rand::thread_rng;
-use rand::seq::SliceRandom;
 use webbrowser;
+use throbber_widgets_tui::ThrobberState;
+
+/// How long the outcome of a command stays in the bottom bar
+const STATUS_DURATION: Duration = Duration::from_secs(5);
+
+/// Everything the app keeps on disk between sessions, opened once by main and handed to the app
+#[derive(Debug)]
+pub struct Stores {
+    pub http_cache: HttpCache,
+    pub full_text_cache: FullTextCache,
+    pub article_store: ArticleStore,
+    pub read: ReadArticles,
+    pub saved: SavedArticles,
+}
+
+impl Stores {
+    /// Opens the stores kept in newsroom's data directory
+    ///
+    /// Arguments
+    /// * dir - The data directory, usually ~/.Newsroom/cache
+    pub fn open(dir: &Path) -> Stores {
+        Stores {
+            http_cache: HttpCache::new(dir.join("http")),
+            full_text_cache: FullTextCache::new(dir.join("fulltext")),
+            article_store: ArticleStore::new(dir.join("articles.json")),
+            read: ReadArticles::load(dir.join("read.json")),
+            saved: SavedArticles::load(dir.join("saved.json")),
+        }
+    }
+
+    /// Stores which keep nothing, for when there is no data directory
+    pub fn disabled() -> Stores {
+        Stores {
+            http_cache: HttpCache::disabled(),
+            full_text_cache: FullTextCache::disabled(),
+            article_store: ArticleStore::disabled(),
+            read: ReadArticles::disabled(),
+            saved: SavedArticles::disabled(),
+        }
+    }
+}
 
 // Application
 #[derive(Debug)]
@@ -23,28 +66,124 @@ pub struct App {
     rx: UnboundedReceiver<NewsroomTransitions>,
     pub tab: u16,
     pub row: Option<usize>,
+    /// The highlighted row of the saved tab
+    pub saved_row: Option<usize>,
+    /// Status of each source in the current (or last) fetch
+    pub fetch_status: Vec<(DataSources, SourceStatus)>,
+    /// Animates the spinners shown while sources are loading
+    pub throbber_state: ThrobberState,
+    /// Remembers feed responses so refreshes only download feeds which changed
+    http_cache: HttpCache,
+    /// Remembers the stories extracted from article pages
+    full_text_cache: FullTextCache,
+    /// Keeps the last fetched articles between sessions
+    article_store: ArticleStore,
+    /// When set, articles are only ever loaded from the article store
+    pub offline: bool,
+    /// The articles the user has already read
+    pub read: ReadArticles,
+    /// The articles the user starred to keep for later
+    pub saved: SavedArticles,
+    /// Narrows down the news list, rows hidden by it are skipped over
+    pub filter: ListFilter,
+    /// Whether the source sidebar is shown next to the news list
+    pub sidebar_open: bool,
+    /// Whether the arrow keys move through the sidebar rather than the news list
+    pub sidebar_focused: bool,
+    /// The highlighted sidebar entry, 0 is "All" and the sources follow in settings order
+    pub sidebar_row: usize,
+    /// The article open in the reader, shown in place of the tabs
+    pub reader: Option<Reader>,
+    /// The width and height of the terminal
+    pub viewport: (u16, u16),
+    /// The form open over the settings tab
+    pub form: Option<Form>,
+    /// The outcome of the last thing done from the settings tab
+    pub settings_message: Option<String>,
+    /// The highlighted source in the settings tab
+    pub settings_row: Option<usize>,
+    /// The theme list in the settings tab, while a theme is being picked
+    pub theme_picker: Option<ThemePicker>,
+    /// The list of keys, shown over everything else
+    pub help: Option<Help>,
+    /// The command line, shown in place of the bottom bar while a command is typed
+    pub command: Option<CommandLine>,
+    /// The outcome of the last command and when it happened, shown in the bottom bar for a while
+    pub status: Option<(String, Instant)>,
 }
 
 impl App {
-    pub fn new() -> App {
+    /// Creates the app on the home screen
+    ///
+    /// Arguments
+    /// * settings - The settings read from newsroom.toml
+    /// * stores - Where fetched, read and saved articles are kept between sessions
+    pub fn new(settings: Settings, stores: Stores) -> App {
         let (tx, rx) = unbounded_channel();
+        let Stores { http_cache, full_text_cache, article_store, read, saved } = stores;
         App {
-            settings: Settings::new(),
+            settings,
             newsroom_state: NewsroomState::Homescreen,
             running: true,
             tx,
             rx,
             tab: 0,
             row: None,
+            saved_row: None,
+            fetch_status: vec![],
+            throbber_state: ThrobberState::default(),
+            http_cache,
+            full_text_cache,
+            article_store,
+            offline: false,
+            read,
+            saved,
+            filter: ListFilter::default(),
+            sidebar_open: false,
+            sidebar_focused: false,
+            sidebar_row: 0,
+            reader: None,
+            viewport: (80, 24),
+            form: None,
+            settings_message: None,
+            settings_row: None,
+            theme_picker: None,
+            help: None,
+            command: None,
+            status: None,
+        }
+    }
+
+    /// Shows the articles stored by the last session right away, then fetches fresh ones unless we are offline
+    pub fn start(&self) {
+        let sources = self.settings.enabled_sources();
+        let stored = self.article_store.load(&sources);
+        if self.offline || !stored.is_empty() {
+            let _ = self.tx.send(NewsroomTransitions::ReturnMedia(stored));
+        }
+        if !self.offline {
+            let _ = self.tx.send(NewsroomTransitions::FetchMedia(sources));
+        }
+    }
+
+    /// Stores the articles we are holding so the next session can start with them
+    fn save_articles(&self) {
+        match &self.newsroom_state {
+            NewsroomState::DisplayMedia(articles) | NewsroomState::ManageSettings(Some(articles)) => self.article_store.save(articles),
+            _ => {},
         }
     }
 
     /// Loads rss articles in the background
+    /// Each source's articles are relayed to the app as soon as that source finishes, followed by a
+    /// 'loaded' transition once every source is done
     /// 
     /// Arguments
     /// * tx - A sender used to relay the 'articles loaded' transition to the app
     /// * sources - The sources which we are fetching rss for 
-    async fn load(tx: UnboundedSender<NewsroomTransitions>, sources: Vec<DataSources>) {
+    /// * policy - The timeout, retry and deadline settings to fetch with
+    /// * cache - Where the last response from each feed is remembered between fetches
+    async fn load(tx: UnboundedSender<NewsroomTransitions>, sources: Vec<DataSources>, policy: FetchPolicy, cache: HttpCache) {
         info!("Initiating article load");
 
         // Send fetch media update over the channel
@@ -52,78 +191,596 @@ impl App {
 
         // Fetch articles and add them to the app
         let num_sources = sources.len();
-        let mut fetched_articles = fetch_articles(sources.to_vec()).await;
+        let num_articles = fetch_articles(sources.to_vec(), policy, cache, tx.clone()).await;
         info!(
             "Loaded {} articles from {} sources, finished article load",
-            fetched_articles.len(),
+            num_articles,
             num_sources
         );
-        let mut rng = thread_rng();
-        fetched_articles.shuffle(&mut rng);
-        let _ = tx.send(NewsroomTransitions::ReturnMedia(fetched_articles));
+        let _ = tx.send(NewsroomTransitions::Loaded);
+    }
+
+    /// Whether any source is still being fetched
+    pub fn is_fetching(&self) -> bool {
+        self.fetch_status.iter().any(|(_, status)| !status.is_finished())
+    }
+
+    /// Starts fetching the given sources in the background
+    fn start_fetch(&mut self, sources: Vec<DataSources>) {
+        self.fetch_status = sources.iter().map(|source| (source.clone(), SourceStatus::Loading)).collect();
+        let local_tx = self.tx.clone();
+        tokio::spawn(App::load(local_tx, sources, self.settings.fetch.clone(), self.http_cache.clone()));
+    }
+
+    /// Merges the articles fetched from a single source into whichever article list we are holding
+    fn collect_source_media(&mut self, source: DataSources, articles: Vec<NewsArticle>) {
+        match &mut self.newsroom_state {
+            NewsroomState::FetchMedia(_) => {
+                // Stay on the loading screen until there is something to show
+                if !articles.is_empty() {
+                    let mut displayed = vec![];
+                    self.row = merge_source_articles(&mut displayed, &source, articles, None, self.settings.sort);
+                    self.newsroom_state = NewsroomState::DisplayMedia(displayed);
+                }
+            },
+            NewsroomState::DisplayMedia(displayed) | NewsroomState::ManageSettings(Some(displayed)) => {
+                self.row = merge_source_articles(displayed, &source, articles, self.row, self.settings.sort);
+            },
+            NewsroomState::ManageSettings(None) => {
+                let mut displayed = vec![];
+                self.row = merge_source_articles(&mut displayed, &source, articles, None, self.settings.sort);
+                self.newsroom_state = NewsroomState::ManageSettings(Some(displayed));
+            },
+            NewsroomState::Homescreen => {},
+        }
     }
 
     /// Advance the current widget (only used now to highlight the next article)
     fn next(&mut self) {
-        match &self.newsroom_state {
-            NewsroomState::DisplayMedia(articles) => {
-                let i = match self.row {
-                    Some(i) => {
-                        if i >= articles.len() - 1 {
-                            0
-                        } else {
-                            i + 1
-                        }
-                    }
-                    None => 0,
-                };
-                self.row = Some(i);
-            }
-            _ => {}
+        if let NewsroomState::DisplayMedia(articles) = &self.newsroom_state {
+            self.row = next_row(self.row, &self.filter.visible(articles));
         }
     }
 
     /// Reverse the current widget (only used now to highlight the previous article)
     fn previous(&mut self) {
+        if let NewsroomState::DisplayMedia(articles) = &self.newsroom_state {
+            self.row = previous_row(self.row, &self.filter.visible(articles));
+        }
+    }
+
+    /// The currently highlighted article, from the saved tab if it is open, otherwise from the news tab
+    pub fn selected_article(&self) -> Option<&NewsArticle> {
         match &self.newsroom_state {
-            NewsroomState::DisplayMedia(articles) => {
-                let i = match self.row {
-                    Some(i) => {
-                        if i == 0 {
-                            articles.len() - 1
-                        } else {
-                            i - 1
-                        }
-                    }
-                    None => 0,
-                };
-                self.row = Some(i);
-            }
-            _ => {}
+            _ if self.tab == 1 => self.saved_row.and_then(|index| self.saved.articles().get(index)),
+            NewsroomState::DisplayMedia(articles) => self.row.and_then(|index| articles.get(index)),
+            _ => None,
         }
     }
 
-    /// Opens the currently highlighted news article in the system browser
+    /// The article the browser key opens: the one in the reader, which stays put while fetches
+    /// move the list's selection around, or else the highlighted one
+    pub fn browser_article(&self) -> Option<&NewsArticle> {
+        match &self.reader {
+            Some(reader) => Some(&reader.article),
+            None => self.selected_article(),
+        }
+    }
+
+    /// Opens the article being read, or the highlighted one, in the system browser and marks it as read
     pub fn open_selected(&self) {
-        match &self.newsroom_state{
-            NewsroomState::DisplayMedia(articles) => {
-                match &self.row {
-                    Some(index) => {
-                        let url = &articles[*index].link;
-                        let _ = webbrowser::open(url);
-                    },
-                    None => {},
+        if let Some(article) = self.browser_article() {
+            let _ = webbrowser::open(&article.link);
+            let _ = self.tx.send(NewsroomTransitions::MarkRead(article.id.clone()));
+        }
+    }
+
+    /// Records the size of the terminal, so the reader knows how far it can scroll
+    ///
+    /// Arguments
+    /// * width - The width of the terminal
+    /// * height - The height of the terminal
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.viewport = (width, height);
+        if let Some(reader) = &mut self.reader {
+            reader.scroll_by(0, self.viewport);
+        }
+        if let Some(help) = &mut self.help {
+            help.scroll_by(0, self.viewport);
+        }
+    }
+
+    /// Opens the currently highlighted article in the reader and marks it as read.
+    /// Sources set to full_text also have the story fetched from the article's page
+    fn open_reader(&mut self) {
+        let Some(article) = self.selected_article().cloned() else { return };
+        self.read.set_read(&article.id, true);
+        // Look the source up in the settings, articles keep a copy of the source from when they were fetched
+        let source = self.settings.sources.iter().find(|source| source.url == article.source.url);
+        let mut reader = Reader::new(article.clone(), &self.settings.theme);
+        if let Some(source) = source.filter(|source| source.full_text && !self.offline) {
+            reader.full_text = FullText::Loading;
+            let tx = self.tx.clone();
+            let timeout = self.settings.fetch.for_source(source).timeout();
+            let cache = self.full_text_cache.clone();
+            tokio::spawn(async move {
+                let result = fetch_full_text(&reqwest::Client::new(), &article.link, timeout, &cache).await;
+                let _ = tx.send(NewsroomTransitions::ReturnFullText(article.id, result));
+            });
+        }
+        self.reader = Some(reader);
+    }
+
+    /// Shows the full story fetched for an article, if it is still open in the reader
+    fn collect_full_text(&mut self, id: String, result: Result<String, FetchError>) {
+        let Some(reader) = self.reader.as_mut().filter(|reader| reader.article.id == id) else { return };
+        match result {
+            Ok(story) => reader.show_full_text(story, &self.settings.theme),
+            Err(e) => {
+                warn!("Couldn't fetch the full text of {}: {}", reader.article.link, e);
+                reader.full_text = FullText::Failed(e.to_string());
+            },
+        }
+    }
+
+    /// Highlights an article by its position in the list being shown and reads it
+    ///
+    /// Arguments
+    /// * index - The position of the article, counting from 0
+    fn open_article(&mut self, index: usize) {
+        let row = match &self.newsroom_state {
+            _ if self.tab == 1 => Some(index).filter(|index| *index < self.saved.articles().len()),
+            NewsroomState::DisplayMedia(articles) => self.filter.visible(articles).get(index).copied(),
+            _ => None,
+        };
+        match row {
+            Some(row) if self.tab == 1 => self.saved_row = Some(row),
+            Some(row) => self.row = Some(row),
+            None => return self.show_status(format!("There is no article {} in the list", index + 1)),
+        }
+        self.open_reader();
+    }
+
+    /// Writes the articles being shown to a markdown digest, the saved articles from the saved tab
+    /// and otherwise the news list as it is filtered
+    ///
+    /// Arguments
+    /// * file - Where to write the digest
+    fn export_digest(&mut self, file: PathBuf) {
+        let articles: Vec<&NewsArticle> = match &self.newsroom_state {
+            _ if self.tab == 1 => self.saved.articles().iter().collect(),
+            NewsroomState::DisplayMedia(articles) | NewsroomState::ManageSettings(Some(articles)) => {
+                self.filter.visible(articles).into_iter().map(|index| &articles[index]).collect()
+            },
+            _ => vec![],
+        };
+        let message = match articles.len() {
+            0 => "There are no articles to export".to_string(),
+            count => {
+                let title = format!("Newsroom digest, {}", chrono::Local::now().format("%Y-%m-%d"));
+                match fs::write(&file, write_digest(&title, &articles)) {
+                    Ok(_) => format!("Exported {} articles to {}", count, file.display()),
+                    Err(e) => format!("Couldn't write {}: {}", file.display(), e),
                 }
             },
-            _ => {},
+        };
+        self.show_status(message);
+    }
+
+    /// Flips the read state of the currently highlighted news article
+    fn toggle_read(&mut self) {
+        if let Some(id) = self.selected_article().map(|article| article.id.clone()) {
+            let read = !self.read.is_read(&id);
+            self.read.set_read(&id, read);
+        }
+    }
+
+    /// Stars the currently highlighted article, or removes it from the saved articles if it is already starred
+    fn toggle_saved(&mut self) {
+        if let Some(article) = self.selected_article().cloned() {
+            self.saved.toggle(&article);
+            // Unstarring from the saved tab shrinks the list under the selection
+            let count = self.saved.articles().len();
+            self.saved_row = match self.saved_row {
+                _ if count == 0 => None,
+                Some(index) => Some(index.min(count - 1)),
+                None => None,
+            };
+        }
+    }
+
+    /// Switches the news list to another sort mode, keeping the highlighted article selected,
+    /// and remembers the choice in newsroom.toml
+    ///
+    /// Arguments
+    /// * sort - The sort mode to switch to
+    fn set_sort(&mut self, sort: SortMode) {
+        self.settings.sort = sort;
+        if let Err(e) = self.settings.save() {
+            warn!("Couldn't save the sort mode: {}", e);
+        }
+        if let NewsroomState::DisplayMedia(articles) | NewsroomState::ManageSettings(Some(articles)) = &mut self.newsroom_state {
+            let selected_id = self.row.and_then(|index| articles.get(index)).map(|article| article.id.clone());
+            self.settings.sort.sort(articles);
+            if let Some(id) = selected_id {
+                self.row = articles.iter().position(|article| article.id == id);
+            }
+        }
+    }
+
+    /// Narrows the news list to a single source, highlighting it in the sidebar
+    ///
+    /// Arguments
+    /// * url - The url of the source, None to show every source's articles
+    fn filter_source(&mut self, url: Option<String>) {
+        self.sidebar_row = url
+            .as_ref()
+            .and_then(|url| self.settings.enabled_sources().iter().position(|source| source.url == *url))
+            .map_or(0, |index| index + 1);
+        self.filter.source_url = url;
+        self.update_search();
+    }
+
+    /// Opens the source sidebar, or closes it if it is already open. Only the news tab has one.
+    /// Closing it shows every source again, since the filter would be invisible otherwise
+    fn toggle_sidebar(&mut self) {
+        if self.tab != 0 {
+            return;
+        }
+        self.sidebar_open = !self.sidebar_open;
+        self.sidebar_focused = self.sidebar_open;
+        if !self.sidebar_open {
+            self.filter_source(None);
+        }
+    }
+
+    /// Moves focus to the sidebar, opening it if needed
+    fn focus_sidebar(&mut self) {
+        if self.tab == 0 {
+            self.sidebar_open = true;
+            self.sidebar_focused = true;
         }
     }
 
-    /// Changes the context tab
+    /// Highlights another sidebar entry and filters the news list to that source
+    ///
+    /// Arguments
+    /// * forward - Whether to move down the sidebar rather than up
+    fn move_sidebar(&mut self, forward: bool) {
+        let sources = self.settings.enabled_sources();
+        let entries = sources.len() + 1;
+        self.sidebar_row = match forward {
+            true => (self.sidebar_row + 1) % entries,
+            false => (self.sidebar_row + entries - 1) % entries,
+        };
+        self.filter.source_url = match self.sidebar_row {
+            0 => None,
+            row => sources.get(row - 1).map(|source| source.url.clone()),
+        };
+        self.update_search();
+    }
+
+    /// Narrows the news list to articles matching the filter, moving the selection
+    /// to the first match if the selected article no longer matches
+    fn update_search(&mut self) {
+        if let NewsroomState::DisplayMedia(articles) = &self.newsroom_state {
+            let visible = self.filter.visible(articles);
+            if !self.row.is_some_and(|row| visible.contains(&row)) {
+                self.row = visible.first().copied();
+            }
+        }
+    }
+
+    /// Opens a form, filled in with a sensible starting point
+    ///
+    /// Arguments
+    /// * action - What the form is for
+    fn open_form(&mut self, action: FormAction) {
+        let opml_file = opml_path().map(|path| path.display().to_string()).unwrap_or_default();
+        self.form = Some(match action {
+            FormAction::ImportOpml => Form::new(action, "Import sources from OPML", vec![("File", opml_file)]),
+            FormAction::ExportOpml => Form::new(action, "Export sources to OPML", vec![("File", opml_file)]),
+            FormAction::AddSource => Form::new(action, "Add a source", vec![("Name (optional, taken from the feed)", String::new()), ("URL", String::new()), ("Category (optional)", String::new())]),
+            FormAction::EditSource(index) => {
+                let Some(source) = self.settings.sources.get(index) else { return };
+                let fields = vec![
+                    ("Name", source.name.clone()),
+                    ("URL", source.url.clone()),
+                    ("Category (optional)", source.category.clone().unwrap_or_default()),
+                ];
+                Form::new(action, &format!("Edit {}", source.name), fields)
+            },
+            FormAction::DeleteSource(index) => {
+                let Some(source) = self.settings.sources.get(index) else { return };
+                Form::new(action, &format!("Delete {}?", source.name), vec![])
+            },
+            FormAction::ChooseFeed { ref feeds, .. } => {
+                let options = feeds
+                    .iter()
+                    .map(|feed| match &feed.title {
+                        Some(title) => format!("{}  {}", title, feed.url),
+                        None => feed.url.clone(),
+                    })
+                    .collect();
+                Form::choice(action, "Choose a feed from the page", options)
+            },
+        });
+    }
+
+    /// Carries out the open form, closing it on success or keeping it open with the reason it failed
+    fn submit_form(&mut self) {
+        let Some(form) = self.form.as_ref().filter(|form| form.checking.is_none()) else { return };
+        let result = match form.action.clone() {
+            FormAction::ImportOpml => {
+                let file = expand_home(form.value(0));
+                self.settings
+                    .import_opml(&file)
+                    .map(|result| result.summary(&file))
+            },
+            FormAction::ExportOpml => {
+                let file = expand_home(form.value(0));
+                self.settings
+                    .export_opml(&file)
+                    .map(|_| format!("Exported {} sources to {}", self.settings.sources.len(), file.display()))
+            },
+            FormAction::AddSource | FormAction::EditSource(_) => {
+                let replacing = match form.action {
+                    FormAction::EditSource(index) => Some(index),
+                    _ => None,
+                };
+                // Editing keeps the settings the form doesn't show, like timeouts
+                let mut source = replacing
+                    .and_then(|index| self.settings.sources.get(index).cloned())
+                    .unwrap_or_else(|| DataSources::new("", ""));
+                source.name = form.value(0).to_string();
+                source.url = form.value(1).to_string();
+                source.category = Some(form.value(2).trim_matches('/').to_string()).filter(|category| !category.is_empty());
+                // Only new urls are checked, so sources can still be renamed while offline
+                let url_changed = replacing
+                    .and_then(|index| self.settings.sources.get(index))
+                    .map(|existing| existing.url.as_str())
+                    != Some(source.url.as_str());
+                match url_changed && !self.offline {
+                    true => return self.check_feed(source, replacing),
+                    false => self.store_source(source, replacing),
+                }
+            },
+            FormAction::ChooseFeed { mut source, replacing, feeds } => {
+                let Some(feed) = feeds.get(form.focus) else { return };
+                source.url = feed.url.clone();
+                return self.check_feed(source, replacing);
+            },
+            FormAction::DeleteSource(index) if index < self.settings.sources.len() => {
+                let removed = self.settings.sources.remove(index);
+                let count = self.settings.sources.len();
+                self.settings_row = self.settings_row.filter(|_| count > 0).map(|row| row.min(count - 1));
+                self.settings.save().map(|_| format!("Deleted {}", removed.name))
+            },
+            FormAction::DeleteSource(_) => Ok(String::new()),
+        };
+        self.finish_form(result);
+    }
+
+    /// Closes the open form and shows what it did, or keeps it open with the reason it failed
+    ///
+    /// Arguments
+    /// * result - The message to show in the settings tab, or the error to show in the form
+    fn finish_form(&mut self, result: Result<String, Box<dyn error::Error>>) {
+        match result {
+            Ok(message) => {
+                self.settings_message = Some(message).filter(|message| !message.is_empty());
+                self.form = None;
+                self.reset_sidebar();
+            },
+            Err(e) => {
+                if let Some(form) = &mut self.form {
+                    form.error = Some(e.to_string());
+                }
+            },
+        }
+    }
+
+    /// Adds or replaces a source and highlights it, returning the message to show for it
+    ///
+    /// Arguments
+    /// * source - The new or edited source
+    /// * replacing - The index of the source being edited, None to add a new source
+    fn store_source(&mut self, source: DataSources, replacing: Option<usize>) -> Result<String, Box<dyn error::Error>> {
+        let name = source.name.clone();
+        self.settings.put_source(source, replacing)?;
+        self.settings_row = Some(replacing.unwrap_or(self.settings.sources.len() - 1));
+        Ok(match replacing {
+            Some(_) => format!("Saved {}", name),
+            None => format!("Added {}, press l on the News tab to fetch it", name),
+        })
+    }
+
+    /// Fetches the url of a source in the background to find out whether it is a feed, keeping the form
+    /// open until the answer comes back. An empty name is filled in from the feed's title afterwards
+    ///
+    /// Arguments
+    /// * source - The new or edited source
+    /// * replacing - The index of the source being edited, None to add a new source
+    fn check_feed(&mut self, source: DataSources, replacing: Option<usize>) {
+        let Some(form) = &mut self.form else { return };
+        let mut named = source.clone();
+        if named.name.is_empty() {
+            named.name = named.url.clone();
+        }
+        if let Err(e) = self.settings.check_source(&named, replacing) {
+            form.error = Some(e.to_string());
+            return;
+        }
+        form.error = None;
+        form.checking = Some(source.url.clone());
+        let tx = self.tx.clone();
+        let timeout = self.settings.fetch.for_source(&source).timeout();
+        tokio::spawn(async move {
+            let result = discover(&reqwest::Client::new(), &source.url, timeout).await;
+            let _ = tx.send(NewsroomTransitions::ReturnFeedCheck(source, replacing, result));
+        });
+    }
+
+    /// Saves a source whose url turned out to be a feed, or offers the feeds found on a web page instead
+    ///
+    /// Arguments
+    /// * source - The source which was checked
+    /// * replacing - The index of the source being edited, None to add a new source
+    /// * result - What was found at the source's url
+    fn collect_feed_check(&mut self, mut source: DataSources, replacing: Option<usize>, result: Result<Discovery, FetchError>) {
+        // The form may have been cancelled while the check was running
+        let Some(form) = self.form.as_mut().filter(|form| form.checking.as_deref() == Some(source.url.as_str())) else { return };
+        form.checking = None;
+        let chosen = matches!(form.action, FormAction::ChooseFeed { .. });
+        let result = match result {
+            Ok(Discovery::Feed(title)) => {
+                if source.name.is_empty() {
+                    source.name = title.filter(|title| !title.trim().is_empty()).unwrap_or_else(|| source.url.clone());
+                }
+                self.store_source(source, replacing)
+            },
+            Ok(Discovery::Page(_)) if chosen => Err(format!("{} is a web page, not a feed", source.url).into()),
+            Ok(Discovery::Page(feeds)) if feeds.is_empty() => Err("No feeds were found on that page".into()),
+            Ok(Discovery::Page(feeds)) => return self.open_form(FormAction::ChooseFeed { source, replacing, feeds }),
+            Err(e) => Err(e.into()),
+        };
+        self.finish_form(result);
+    }
+
+    /// Starts picking a theme from the settings tab
+    fn open_theme_picker(&mut self) {
+        self.theme_picker = Some(ThemePicker::new(&self.settings));
+    }
+
+    /// Highlights another theme and previews it across the app
+    ///
+    /// Arguments
+    /// * forward - Whether to move down the theme list rather than up
+    fn move_theme(&mut self, forward: bool) {
+        let Some(picker) = &mut self.theme_picker else { return };
+        picker.step(forward);
+        self.settings.theme = match picker.name() {
+            name if name == picker.original.name => picker.original.clone(),
+            name => match Theme::load(name, &self.settings.custom_themes) {
+                Ok(theme) => theme,
+                Err(e) => {
+                    self.settings_message = Some(format!("Couldn't load the theme: {}", e));
+                    return;
+                },
+            },
+        };
+        self.settings_message = None;
+    }
+
+    /// Keeps the previewed theme and saves it to newsroom.toml
+    fn confirm_theme(&mut self) {
+        if self.theme_picker.take().is_none() {
+            return;
+        }
+        self.settings_message = match self.settings.save_theme() {
+            Ok(_) => Some(format!("Switched to the {} theme", self.settings.theme.name)),
+            Err(e) => Some(format!("Couldn't save newsroom.toml: {}", e)),
+        };
+    }
+
+    /// Stops picking a theme and puts back the one in use before
+    fn cancel_theme_picker(&mut self) {
+        if let Some(picker) = self.theme_picker.take() {
+            self.settings.theme = picker.original;
+            self.settings_message = None;
+        }
+    }
+
+    /// Switches to a theme and saves it to newsroom.toml
+    ///
+    /// Arguments
+    /// * name - The name of the theme
+    fn set_theme(&mut self, name: &str) {
+        let message = match Theme::load(name, &self.settings.custom_themes) {
+            Ok(theme) => {
+                self.settings.theme = theme;
+                match self.settings.save_theme() {
+                    Ok(_) => format!("Switched to the {} theme", name),
+                    Err(e) => format!("Couldn't save newsroom.toml: {}", e),
+                }
+            },
+            Err(e) => format!("Couldn't load the theme: {}", e),
+        };
+        self.show_status(message);
+    }
+
+    /// Carries out the command typed into the command line through the transitions it stands for,
+    /// keeping the command line open with the reason if it can't be carried out
+    fn submit_command(&mut self) {
+        let Some(command) = &mut self.command else { return };
+        match command::parse(&command.input, &self.settings) {
+            // The search line belongs to the news list, so its query can't be typed from another tab
+            Ok(transitions) if self.tab != 0 && matches!(transitions.first(), Some(NewsroomTransitions::StartSearch)) => {
+                command.error = Some("Searching only works on the News tab".to_string());
+            },
+            Ok(transitions) => {
+                self.command = None;
+                for transition in transitions {
+                    let _ = self.tx.send(transition);
+                }
+            },
+            Err(e) => command.error = Some(e),
+        }
+    }
+
+    /// Shows the outcome of a command in the bottom bar
+    fn show_status(&mut self, message: String) {
+        self.status = Some((message, Instant::now()));
+    }
+
+    /// Enables the highlighted source in the settings tab if it is disabled, or disables it
+    fn toggle_source(&mut self) {
+        let Some(source) = self.settings_row.and_then(|row| self.settings.sources.get_mut(row)) else { return };
+        source.disabled = !source.disabled;
+        let message = match source.disabled {
+            true => format!("Disabled {}", source.name),
+            false => format!("Enabled {}, press l on the News tab to fetch it", source.name),
+        };
+        self.save_sources(message);
+    }
+
+    /// Swaps the highlighted source in the settings tab with one of its neighbours, keeping it highlighted
+    ///
+    /// Arguments
+    /// * down - Whether to move the source down the list rather than up
+    fn move_source(&mut self, down: bool) {
+        let Some(row) = self.settings_row else { return };
+        let target = match down {
+            true if row + 1 < self.settings.sources.len() => row + 1,
+            false if row > 0 => row - 1,
+            _ => return,
+        };
+        self.settings.sources.swap(row, target);
+        self.settings_row = Some(target);
+        self.save_sources(String::new());
+    }
+
+    /// Writes changed sources to newsroom.toml, reporting the outcome in the settings tab
+    fn save_sources(&mut self, message: String) {
+        self.settings_message = match self.settings.save() {
+            Ok(_) => Some(message).filter(|message| !message.is_empty()),
+            Err(e) => Some(format!("Couldn't save newsroom.toml: {}", e)),
+        };
+        self.reset_sidebar();
+    }
+
+    /// Shows every source again after the sources change, so the sidebar never points at a missing source
+    fn reset_sidebar(&mut self) {
+        self.sidebar_row = 0;
+        self.filter.source_url = None;
+    }
+
+    /// Changes the context tab, cycling from news to saved to settings
     pub fn change_tab(&self) {
         let _ = match &self.newsroom_state {
             NewsroomState::ManageSettings(_) => self.tx.send(NewsroomTransitions::ExitSettings),
-            _ => self.tx.send(NewsroomTransitions::ToSettings),
+            _ if self.tab == 1 => self.tx.send(NewsroomTransitions::ToSettings),
+            _ => self.tx.send(NewsroomTransitions::ToSaved),
         };
     }
 
@@ -133,23 +790,37 @@ impl App {
     /// * `transition` - The state transition to be acted upon
     fn collect(&mut self, transition: NewsroomTransitions) {
         match (&self.newsroom_state, transition) {
-            (NewsroomState::Homescreen, NewsroomTransitions::Loaded) => todo!(),
-            (NewsroomState::Homescreen, NewsroomTransitions::ExitSettings) => todo!(),
+            (_, NewsroomTransitions::FetchMedia(_)) if self.offline => info!("Offline, not fetching articles"),
+            // Leaving settings without articles comes back here, a fetch finishing afterwards has nothing left to store
+            (NewsroomState::Homescreen, NewsroomTransitions::Loaded) => {},
+            // Only sent from the settings tab, one left over from before the state changed just shows the news tab
+            (NewsroomState::Homescreen | NewsroomState::FetchMedia(_) | NewsroomState::DisplayMedia(_), NewsroomTransitions::ExitSettings) => self.tab = 0,
             (NewsroomState::Homescreen, NewsroomTransitions::FetchMedia(sources)) => {
                 self.newsroom_state = NewsroomState::FetchMedia(sources.clone());
-                let local_tx = self.tx.clone();
-                tokio::spawn(App::load(local_tx, sources));
+                self.start_fetch(sources);
+            },
+            // Articles stored by the last session
+            (NewsroomState::Homescreen, NewsroomTransitions::ReturnMedia(mut media_vec)) => {
+                self.settings.sort.sort(&mut media_vec);
+                self.newsroom_state = NewsroomState::DisplayMedia(media_vec);
             },
-            (NewsroomState::Homescreen, NewsroomTransitions::ReturnMedia(_)) => todo!(),
-            (NewsroomState::FetchMedia(_), NewsroomTransitions::Loaded) => todo!(),
-            (NewsroomState::FetchMedia(_), NewsroomTransitions::ExitSettings) => todo!(),
+            // Every source finished without returning any articles
+            (NewsroomState::FetchMedia(_), NewsroomTransitions::Loaded) => self.newsroom_state = NewsroomState::DisplayMedia(vec![]),
             (NewsroomState::FetchMedia(_), NewsroomTransitions::FetchMedia(_)) => {},
-            (NewsroomState::FetchMedia(_), NewsroomTransitions::ReturnMedia(media_vec)) =>  self.newsroom_state = NewsroomState::DisplayMedia(media_vec),
-            (NewsroomState::DisplayMedia(_), NewsroomTransitions::Loaded) => todo!(),
-            (NewsroomState::DisplayMedia(_), NewsroomTransitions::ExitSettings) => todo!(),
-            (NewsroomState::DisplayMedia(_), NewsroomTransitions::FetchMedia(_)) => todo!(),
+            (NewsroomState::FetchMedia(_), NewsroomTransitions::ReturnMedia(mut media_vec)) => {
+                self.settings.sort.sort(&mut media_vec);
+                self.newsroom_state = NewsroomState::DisplayMedia(media_vec);
+            },
+            (NewsroomState::DisplayMedia(_), NewsroomTransitions::Loaded) => self.save_articles(),
+            // Refresh in place, keeping the current articles until each source returns. The settings tab holds
+            // the articles too, so refreshing from there updates them for when the news tab is shown again
+            (NewsroomState::DisplayMedia(_) | NewsroomState::ManageSettings(_), NewsroomTransitions::FetchMedia(sources)) => {
+                if !self.is_fetching() {
+                    self.start_fetch(sources);
+                }
+            },
             (NewsroomState::DisplayMedia(_), NewsroomTransitions::ReturnMedia(_)) => {}
-            (NewsroomState::ManageSettings(_), NewsroomTransitions::Loaded) => todo!(),
+            (NewsroomState::ManageSettings(_), NewsroomTransitions::Loaded) => self.save_articles(),
             (NewsroomState::ManageSettings(maybe_articles), NewsroomTransitions::ExitSettings) => {
                 self.tab = 0; 
                 // If we saved the articles when transitioning to settings, change to the display state on settings exit
@@ -160,53 +831,247 @@ impl App {
                 }
             
             },
-            (NewsroomState::DisplayMedia(articles), NewsroomTransitions::ToSettings) => {self.tab = 1; self.newsroom_state = NewsroomState::ManageSettings(Some(articles.to_vec()))},
-            (_, NewsroomTransitions::ToSettings) => {self.tab = 1; self.newsroom_state = NewsroomState::ManageSettings(None)},
-            (NewsroomState::ManageSettings(_), NewsroomTransitions::FetchMedia(_)) => todo!(),
-            (NewsroomState::ManageSettings(_), NewsroomTransitions::ReturnMedia(_)) => todo!(),
-            (NewsroomState::Homescreen, NewsroomTransitions::Up) => todo!(),
-            (NewsroomState::Homescreen, NewsroomTransitions::Down) => todo!(),
-            (NewsroomState::Homescreen, NewsroomTransitions::Left) => todo!(),
-            (NewsroomState::Homescreen, NewsroomTransitions::Right) => todo!(),
-            (NewsroomState::FetchMedia(_), NewsroomTransitions::Up) => todo!(),
-            (NewsroomState::FetchMedia(_), NewsroomTransitions::Down) => todo!(),
-            (NewsroomState::FetchMedia(_), NewsroomTransitions::Left) => todo!(),
-            (NewsroomState::FetchMedia(_), NewsroomTransitions::Right) => todo!(),
+            (NewsroomState::ManageSettings(_), NewsroomTransitions::ToSettings) => {},
+            // The sidebar is only on the news tab, so the other tabs take the arrow keys and enter
+            (NewsroomState::DisplayMedia(articles), NewsroomTransitions::ToSettings) => {
+                self.tab = 2;
+                self.sidebar_focused = false;
+                self.newsroom_state = NewsroomState::ManageSettings(Some(articles.to_vec()));
+            },
+            (_, NewsroomTransitions::ToSettings) => {
+                self.tab = 2;
+                self.sidebar_focused = false;
+                self.newsroom_state = NewsroomState::ManageSettings(None);
+            },
+            // The saved tab leaves the news state alone so fetches carry on underneath it
+            (_, NewsroomTransitions::ToSaved) => {
+                self.tab = 1;
+                self.sidebar_focused = false;
+            },
+            (_, NewsroomTransitions::Up) if self.theme_picker.is_some() => self.move_theme(false),
+            (_, NewsroomTransitions::Down) if self.theme_picker.is_some() => self.move_theme(true),
+            (_, NewsroomTransitions::Up) if self.tab == 1 => self.saved_row = previous_row(self.saved_row, &(0..self.saved.articles().len()).collect_vec()),
+            (_, NewsroomTransitions::Down) if self.tab == 1 => self.saved_row = next_row(self.saved_row, &(0..self.saved.articles().len()).collect_vec()),
+            (NewsroomState::ManageSettings(_), NewsroomTransitions::ReturnMedia(_)) => {},
+            (NewsroomState::Homescreen, NewsroomTransitions::Up) => {},
+            (NewsroomState::Homescreen, NewsroomTransitions::Down) => {},
+            (NewsroomState::Homescreen, NewsroomTransitions::Left) => {},
+            (NewsroomState::Homescreen, NewsroomTransitions::Right) => {},
+            (NewsroomState::FetchMedia(_), NewsroomTransitions::Up) => {},
+            (NewsroomState::FetchMedia(_), NewsroomTransitions::Down) => {},
+            (NewsroomState::FetchMedia(_), NewsroomTransitions::Left) => {},
+            (NewsroomState::FetchMedia(_), NewsroomTransitions::Right) => {},
+            (NewsroomState::DisplayMedia(_), NewsroomTransitions::Up) if self.sidebar_focused => self.move_sidebar(false),
+            (NewsroomState::DisplayMedia(_), NewsroomTransitions::Down) if self.sidebar_focused => self.move_sidebar(true),
             (NewsroomState::DisplayMedia(_), NewsroomTransitions::Up) => self.previous(),
             (NewsroomState::DisplayMedia(_), NewsroomTransitions::Down) => self.next(),
-            (NewsroomState::DisplayMedia(_), NewsroomTransitions::Left) => todo!(),
-            (NewsroomState::DisplayMedia(_), NewsroomTransitions::Right) => todo!(),
-            (NewsroomState::ManageSettings(_), NewsroomTransitions::Up) => {},
-            (NewsroomState::ManageSettings(_), NewsroomTransitions::Down) => {},
+            (NewsroomState::DisplayMedia(_), NewsroomTransitions::Left) => self.focus_sidebar(),
+            (NewsroomState::DisplayMedia(_), NewsroomTransitions::Right) => self.sidebar_focused = false,
+            (NewsroomState::ManageSettings(_), NewsroomTransitions::Up) => self.settings_row = previous_row(self.settings_row, &(0..self.settings.sources.len()).collect_vec()),
+            (NewsroomState::ManageSettings(_), NewsroomTransitions::Down) => self.settings_row = next_row(self.settings_row, &(0..self.settings.sources.len()).collect_vec()),
             (NewsroomState::ManageSettings(_), NewsroomTransitions::Left) => {},
             (NewsroomState::ManageSettings(_), NewsroomTransitions::Right) => {},
+            (_, NewsroomTransitions::ReturnSourceMedia(source, articles)) => self.collect_source_media(source, articles),
+            (_, NewsroomTransitions::SourceStatus(source, status)) => self.update_source_status(source, status),
+            (_, NewsroomTransitions::ToggleRead) => self.toggle_read(),
+            (_, NewsroomTransitions::MarkRead(id)) => self.read.set_read(&id, true),
+            (_, NewsroomTransitions::ToggleSaved) => self.toggle_saved(),
+            (_, NewsroomTransitions::CycleSort) => self.set_sort(self.settings.sort.next()),
+            (_, NewsroomTransitions::SetSort(sort)) => self.set_sort(sort),
+            (_, NewsroomTransitions::FilterSource(url)) => self.filter_source(url),
+            (_, NewsroomTransitions::OpenArticle(index)) => self.open_article(index),
+            (_, NewsroomTransitions::ExportDigest(file)) => self.export_digest(file),
+            (_, NewsroomTransitions::ToggleSidebar) => self.toggle_sidebar(),
+            (_, NewsroomTransitions::StartSearch) if self.tab == 0 => {
+                self.filter.query.clear();
+                self.filter.editing = true;
+            },
+            (_, NewsroomTransitions::StartSearch) => {},
+            (_, NewsroomTransitions::SearchInput(c)) => {
+                self.filter.query.push(c);
+                self.update_search();
+            },
+            (_, NewsroomTransitions::SearchBackspace) => {
+                self.filter.query.pop();
+                self.update_search();
+            },
+            (_, NewsroomTransitions::ConfirmSearch) => self.filter.editing = false,
+            // The selection is kept, so it stays on the same article as the full list comes back
+            (_, NewsroomTransitions::CancelSearch) => {
+                self.filter.query.clear();
+                self.filter.editing = false;
+            },
+            // Without a search there is nothing to jump between, so n doesn't quietly act like j
+            (_, NewsroomTransitions::NextMatch) if self.filter.is_active() => self.next(),
+            (_, NewsroomTransitions::PreviousMatch) if self.filter.is_active() => self.previous(),
+            (_, NewsroomTransitions::NextMatch | NewsroomTransitions::PreviousMatch) => {},
+            (_, NewsroomTransitions::OpenReader) => self.open_reader(),
+            (_, NewsroomTransitions::CloseReader) => self.reader = None,
+            (_, NewsroomTransitions::ReturnFullText(id, result)) => self.collect_full_text(id, result),
+            (NewsroomState::ManageSettings(_), NewsroomTransitions::OpenForm(action)) => self.open_form(action),
+            (_, NewsroomTransitions::OpenForm(_)) => {},
+            (_, NewsroomTransitions::FormInput(c)) => self.form.iter_mut().for_each(|form| form.input(c)),
+            (_, NewsroomTransitions::FormBackspace) => self.form.iter_mut().for_each(|form| form.backspace()),
+            (_, NewsroomTransitions::FormSet(value)) => self.form.iter_mut().for_each(|form| form.set(&value)),
+            (_, NewsroomTransitions::NextField) => self.form.iter_mut().for_each(|form| form.next_field()),
+            (_, NewsroomTransitions::PreviousField) => self.form.iter_mut().for_each(|form| form.previous_field()),
+            (_, NewsroomTransitions::SubmitForm) => self.submit_form(),
+            (_, NewsroomTransitions::CancelForm) => self.form = None,
+            (_, NewsroomTransitions::ReturnFeedCheck(source, replacing, result)) => self.collect_feed_check(source, replacing, result),
+            (NewsroomState::ManageSettings(_), NewsroomTransitions::ToggleSource) => self.toggle_source(),
+            (NewsroomState::ManageSettings(_), NewsroomTransitions::MoveSourceUp) => self.move_source(false),
+            (NewsroomState::ManageSettings(_), NewsroomTransitions::MoveSourceDown) => self.move_source(true),
+            (_, NewsroomTransitions::ToggleSource | NewsroomTransitions::MoveSourceUp | NewsroomTransitions::MoveSourceDown) => {},
+            (NewsroomState::ManageSettings(_), NewsroomTransitions::OpenThemePicker) => self.open_theme_picker(),
+            (_, NewsroomTransitions::OpenThemePicker) => {},
+            (_, NewsroomTransitions::ConfirmTheme) => self.confirm_theme(),
+            (_, NewsroomTransitions::CancelThemePicker) => self.cancel_theme_picker(),
+            (_, NewsroomTransitions::SetTheme(name)) => self.set_theme(&name),
+            (_, NewsroomTransitions::OpenCommand) => {
+                self.command = Some(CommandLine::default());
+                self.status = None;
+            },
+            (_, NewsroomTransitions::CommandInput(c)) => self.command.iter_mut().for_each(|command| command.input(c)),
+            (_, NewsroomTransitions::CommandBackspace) => self.command.iter_mut().for_each(|command| command.backspace()),
+            (_, NewsroomTransitions::CompleteCommand) => {
+                if let Some(command) = &mut self.command {
+                    command.complete(&self.settings);
+                }
+            },
+            (_, NewsroomTransitions::SubmitCommand) => self.submit_command(),
+            (_, NewsroomTransitions::CancelCommand) => self.command = None,
+            (_, NewsroomTransitions::ToggleHelp) => {
+                self.help = match self.help {
+                    Some(_) => None,
+                    None => Some(Help::new(&self.settings.keymap, &self.settings.theme)),
+                };
+            },
+            (_, NewsroomTransitions::ScrollHelp(rows)) => {
+                if let Some(help) = &mut self.help {
+                    help.scroll_by(rows, self.viewport);
+                }
+            },
+            (_, NewsroomTransitions::ScrollReader(rows)) => {
+                if let Some(reader) = &mut self.reader {
+                    reader.scroll_by(rows, self.viewport);
+                }
+            },
             (_, NewsroomTransitions::Quit) => self.running = false,
         }
     }
 
+    /// Records the outcome of fetching a single source
+    fn update_source_status(&mut self, source: DataSources, status: SourceStatus) {
+        let entry = self.fetch_status
+            .iter_mut()
+            .find(|(fetching, current)| fetching.url == source.url && !current.is_finished());
+        match entry {
+            Some((_, current)) => *current = status,
+            None => self.fetch_status.push((source, status)),
+        }
+    }
+
     pub fn tick(&mut self) {
         // Used whenever the tick is nessasary
+        self.throbber_state.calc_next();
+        if self.status.as_ref().is_some_and(|(_, shown)| shown.elapsed() > STATUS_DURATION) {
+            self.status = None;
+        }
     }
 
     /// Collects state transitions from the input channel and runs collect on them. It is intended to be run in the main loop
-    pub async fn poll_and_run_action(&mut self){
+    /// Returns whether a transition was run, so callers can drain the channel
+    pub async fn poll_and_run_action(&mut self) -> bool {
         let transition_maybe = self.rx.try_recv();
         match transition_maybe{
-            Ok(transition) => self.collect(transition),
-            Err(_) => {},
+            Ok(transition) => {
+                self.collect(transition);
+                true
+            },
+            Err(_) => false,
         }
     }
 }
 
+/// The first row after the given one out of the rows being shown, wrapping around to the top
+///
+/// Arguments
+/// * row - The currently selected row
+/// * rows - The rows being shown, in order
+fn next_row(row: Option<usize>, rows: &[usize]) -> Option<usize> {
+    match row {
+        Some(current) => rows.iter().find(|&&visible| visible > current).or(rows.first()).copied(),
+        None => rows.first().copied(),
+    }
+}
+
+/// The last row before the given one out of the rows being shown, wrapping around to the bottom
+///
+/// Arguments
+/// * row - The currently selected row
+/// * rows - The rows being shown, in order
+fn previous_row(row: Option<usize>, rows: &[usize]) -> Option<usize> {
+    match row {
+        Some(current) => rows.iter().rev().find(|&&visible| visible < current).or(rows.last()).copied(),
+        None => rows.first().copied(),
+    }
+}
+
+/// Replaces the articles previously provided by a source with freshly fetched ones, then sorts the list.
+/// Returns where the previously selected article ended up so the selection doesn't jump around
+///
+/// Arguments
+/// * articles - The list of articles to merge into
+/// * source - The source the fresh articles were fetched from
+/// * fresh - The freshly fetched articles
+/// * selected - The currently selected row in `articles`
+/// * sort - The order to keep the list in
+fn merge_source_articles(articles: &mut Vec<NewsArticle>, source: &DataSources, fresh: Vec<NewsArticle>, selected: Option<usize>, sort: SortMode) -> Option<usize> {
+    let selected_link = selected.and_then(|index| articles.get(index)).map(|article| article.link.clone());
+
+    articles.retain(|article| article.source.url != source.url);
+    articles.extend(fresh);
+    sort.sort(articles);
+
+    match (selected, selected_link) {
+        (_, _) if articles.is_empty() => None,
+        (Some(index), Some(link)) => articles
+            .iter()
+            .position(|article| article.link == link)
+            .or(Some(index.min(articles.len() - 1))),
+        (selected, _) => selected,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::newsroomcore::{storage::TempPath, stubserver::{StubServer, StubResponse}};
+    use crate::handler::handle_key_events;
+    use crate::keymap::Keymap;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    /// An app which keeps nothing on disk and has no sources
+    fn test_app() -> App {
+        App::new(Settings::test(vec![]), Stores::disabled())
+    }
+
+    /// A source served by the stub server, so fetching it needs no network
+    async fn stub_source() -> (StubServer, DataSources) {
+        let server = StubServer::start(vec![
+            ("/feed", vec![StubResponse::ok(&include_bytes!("../resources/fixtures/rss.xml")[..])]),
+        ]).await;
+        let source = DataSources::new("stub", &server.url("/feed"));
+        (server, source)
+    }
+
     /// Test that we're able to run the load fn correctly
     #[tokio::test]
     async fn test_load() {
-        let mut app: App = App::new();
+        let (_server, source) = stub_source().await;
+        let mut app: App = test_app();
         assert!(matches!(app.newsroom_state, NewsroomState::Homescreen));
-        App::load(app.tx.clone() ,vec![app.settings.sources[0].clone()]).await;
+        App::load(app.tx.clone(), vec![source], app.settings.fetch.clone(), HttpCache::disabled()).await;
         
         // Check that we first get a state change to the fetch state
         app.poll_and_run_action().await;
@@ -214,22 +1079,63 @@ mod test {
             app.newsroom_state,
             NewsroomState::FetchMedia(_)
         ));
-        // Check that we then get a state change to the display state
-        app.poll_and_run_action().await;
+        assert!(app.is_fetching());
+        // Check that once the source reports back we get a state change to the display state
+        while app.poll_and_run_action().await {}
         assert!(matches!(
             app.newsroom_state,
             NewsroomState::DisplayMedia(_)
         ));
+        assert!(!app.is_fetching());
+    }
+
+    // Test that articles are shown as soon as the first source returns and that the list stays navigable
+    #[tokio::test]
+    async fn test_progressive_load(){
+        let mut app: App = test_app();
+        let fast = DataSources::new("fast", "http://fast.example.com");
+        let slow = DataSources::new("slow", "http://slow.example.com");
+        app.newsroom_state = NewsroomState::FetchMedia(vec![fast.clone(), slow.clone()]);
+        app.fetch_status = vec![(fast.clone(), SourceStatus::Loading), (slow.clone(), SourceStatus::Loading)];
+
+        app.collect(NewsroomTransitions::ReturnSourceMedia(fast.clone(), vec![NewsArticle::test(&fast, "a"), NewsArticle::test(&fast, "b")]));
+        assert!(matches!(&app.newsroom_state, NewsroomState::DisplayMedia(articles) if articles.len() == 2));
+
+        app.collect(NewsroomTransitions::Down);
+        app.collect(NewsroomTransitions::Down);
+        let NewsroomState::DisplayMedia(articles) = &app.newsroom_state else { panic!() };
+        let selected_link = articles[app.row.unwrap()].link.clone();
+
+        // The slow source arriving doesn't move the selection off the selected article
+        app.collect(NewsroomTransitions::ReturnSourceMedia(slow.clone(), vec![NewsArticle::test(&slow, "c"), NewsArticle::test(&slow, "d")]));
+        let NewsroomState::DisplayMedia(articles) = &app.newsroom_state else { panic!() };
+        assert_eq!(articles.len(), 4);
+        assert_eq!(articles[app.row.unwrap()].link, selected_link);
+
+        // Refetching a source replaces its articles rather than duplicating them
+        app.collect(NewsroomTransitions::ReturnSourceMedia(fast.clone(), vec![NewsArticle::test(&fast, "e")]));
+        let NewsroomState::DisplayMedia(articles) = &app.newsroom_state else { panic!() };
+        assert_eq!(articles.len(), 3);
+        assert!(articles.iter().all(|article| article.link != "a" && article.link != "b"));
+
+        // Even once its settings have been edited, as long as the url is the same
+        let mut edited = fast.clone();
+        edited.name = "faster".to_string();
+        edited.timeout_secs = Some(5);
+        app.collect(NewsroomTransitions::ReturnSourceMedia(edited.clone(), vec![NewsArticle::test(&edited, "f")]));
+        let NewsroomState::DisplayMedia(articles) = &app.newsroom_state else { panic!() };
+        assert_eq!(articles.len(), 3);
+        assert!(articles.iter().all(|article| article.link != "e"));
     }
 
     #[tokio::test]
     async fn test_up_down(){
-        let mut app: App = App::new();
+        let (_server, source) = stub_source().await;
+        let mut app: App = test_app();
         assert!(matches!(app.newsroom_state, NewsroomState::Homescreen));
-        App::load(app.tx.clone() ,vec![app.settings.sources[0].clone()]).await;
+        App::load(app.tx.clone(), vec![source], app.settings.fetch.clone(), HttpCache::disabled()).await;
 
-        app.poll_and_run_action().await; // Wait fetch state
-        app.poll_and_run_action().await; // Wait display state
+        while app.poll_and_run_action().await {} // Wait display state
 
         // We now should be in a display state
         // Check that the up/down keys work now to advance the rows
@@ -248,21 +1154,688 @@ mod test {
 
     #[tokio::test]
     async fn test_tab(){
-        let mut app: App = App::new();
+        let (_server, source) = stub_source().await;
+        let mut app: App = test_app();
         assert!(matches!(app.newsroom_state, NewsroomState::Homescreen));
-        App::load(app.tx.clone() ,vec![app.settings.sources[0].clone()]).await;
+        App::load(app.tx.clone(), vec![source], app.settings.fetch.clone(), HttpCache::disabled()).await;
 
-        app.poll_and_run_action().await; // Wait fetch state
-        app.poll_and_run_action().await; // Wait display state
+        while app.poll_and_run_action().await {} // Wait display state
 
         // We now should be in a display state
         // Check that we can tab out to the settings screen and then back to the main screen
 
         app.change_tab();
         app.poll_and_run_action().await;
+        assert_eq!(app.tab, 1);
+        assert!(matches!(app.newsroom_state, NewsroomState::DisplayMedia(_)));
+        app.change_tab();
+        app.poll_and_run_action().await;
+        assert_eq!(app.tab, 2);
         assert!(matches!(app.newsroom_state, NewsroomState::ManageSettings(_)));
         app.change_tab();
         app.poll_and_run_action().await;
+        assert_eq!(app.tab, 0);
         assert!(matches!(app.newsroom_state, NewsroomState::DisplayMedia(_)));
     }
+
+    // Test that the home screen, reached by leaving settings before any articles arrived, ignores the keys for the list
+    #[tokio::test]
+    async fn test_homescreen(){
+        let mut app = test_app();
+        app.collect(NewsroomTransitions::ToSettings);
+        app.collect(NewsroomTransitions::ExitSettings);
+        assert!(matches!(app.newsroom_state, NewsroomState::Homescreen));
+        assert_eq!(app.tab, 0);
+        for transition in [NewsroomTransitions::Up, NewsroomTransitions::Down, NewsroomTransitions::Loaded, NewsroomTransitions::ExitSettings] {
+            app.collect(transition);
+        }
+        assert!(matches!(app.newsroom_state, NewsroomState::Homescreen));
+        assert_eq!(app.row, None);
+    }
+
+    // Test that offline mode shows the stored articles and never fetches
+    #[tokio::test]
+    async fn test_offline_start(){
+        let path = TempPath::new("offline.json");
+        let mut app = test_app();
+        let source = DataSources::new("a", "http://a.example.com");
+        app.settings.sources = vec![source.clone()];
+        app.article_store = ArticleStore::new(path.to_path_buf());
+        app.article_store.save(&[NewsArticle::test(&source, "a"), NewsArticle::test(&source, "b")]);
+        app.offline = true;
+
+        app.start();
+        while app.poll_and_run_action().await {}
+        assert!(matches!(&app.newsroom_state, NewsroomState::DisplayMedia(articles) if articles.len() == 2));
+
+        // Refreshing does nothing
+        let _ = app.tx.send(NewsroomTransitions::FetchMedia(app.settings.sources.clone()));
+        while app.poll_and_run_action().await {}
+        assert!(!app.is_fetching());
+    }
+
+    // Test that refreshing from the settings tab fetches into the articles it holds for the news tab
+    #[tokio::test]
+    async fn test_refresh_from_settings(){
+        let (_server, source) = stub_source().await;
+        let mut app = test_app();
+        app.settings.sources = vec![source.clone()];
+        app.newsroom_state = NewsroomState::DisplayMedia(vec![NewsArticle::test(&source, "stale")]);
+        app.collect(NewsroomTransitions::ToSettings);
+
+        run_command(&mut app, "refresh").await;
+        assert!(app.command.is_none() && app.is_fetching());
+        for _ in 0..500 {
+            while app.poll_and_run_action().await {}
+            if !app.is_fetching() && app.rx.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let NewsroomState::ManageSettings(Some(articles)) = &app.newsroom_state else { panic!() };
+        assert!(!articles.is_empty());
+        assert!(articles.iter().all(|article| article.link != "stale"));
+        assert_eq!(app.tab, 2);
+    }
+
+    // Test that stored articles are shown immediately, then replaced by fresh ones which are stored in turn
+    #[tokio::test]
+    async fn test_stored_then_fetched(){
+        let (_server, source) = stub_source().await;
+        let path = TempPath::new("stored.json");
+        let mut app = test_app();
+        app.settings.sources = vec![source.clone()];
+        app.article_store = ArticleStore::new(path.to_path_buf());
+        app.article_store.save(&[NewsArticle::test(&source, "stale")]);
+
+        app.start();
+        app.poll_and_run_action().await;
+        assert!(matches!(&app.newsroom_state, NewsroomState::DisplayMedia(articles) if articles[0].link == "stale"));
+
+        // Wait for the fetch to finish
+        let mut saw_fetching = false;
+        for _ in 0..500 {
+            while app.poll_and_run_action().await {}
+            saw_fetching |= app.is_fetching();
+            if saw_fetching && !app.is_fetching() && app.rx.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        while app.poll_and_run_action().await {}
+
+        let NewsroomState::DisplayMedia(articles) = &app.newsroom_state else { panic!() };
+        assert!(!articles.is_empty());
+        assert!(articles.iter().all(|article| article.link != "stale"));
+        let stored = app.article_store.load(&app.settings.sources);
+        assert_eq!(stored.len(), articles.len());
+    }
+
+    // Test that the selected article's read state can be toggled and that opening marks it read
+    #[tokio::test]
+    async fn test_read_tracking(){
+        let mut app = test_app();
+        let source = DataSources::new("source", "http://example.com");
+        app.newsroom_state = NewsroomState::DisplayMedia(vec![NewsArticle::test(&source, "a"), NewsArticle::test(&source, "b")]);
+
+        // Nothing selected, nothing to toggle
+        app.collect(NewsroomTransitions::ToggleRead);
+        assert!(!app.read.is_read("a") && !app.read.is_read("b"));
+
+        app.collect(NewsroomTransitions::Down);
+        app.collect(NewsroomTransitions::ToggleRead);
+        assert!(app.read.is_read("a"));
+        app.collect(NewsroomTransitions::ToggleRead);
+        assert!(!app.read.is_read("a"));
+
+        app.collect(NewsroomTransitions::MarkRead("b".to_string()));
+        assert!(app.read.is_read("b"));
+    }
+
+    // Test that starred articles show up in the saved tab, outlive the feed and can be unstarred from there
+    #[tokio::test]
+    async fn test_saved_articles(){
+        let mut app = test_app();
+        let source = DataSources::new("source", "http://example.com");
+        app.newsroom_state = NewsroomState::DisplayMedia(vec![NewsArticle::test(&source, "a"), NewsArticle::test(&source, "b")]);
+
+        app.collect(NewsroomTransitions::Down);
+        app.collect(NewsroomTransitions::ToggleSaved);
+        app.collect(NewsroomTransitions::Down);
+        app.collect(NewsroomTransitions::ToggleSaved);
+        assert!(app.saved.is_saved("a") && app.saved.is_saved("b"));
+
+        // The feed moving on doesn't drop saved articles
+        app.collect(NewsroomTransitions::ReturnSourceMedia(source.clone(), vec![]));
+        app.collect(NewsroomTransitions::ToSaved);
+        assert_eq!(app.saved.articles().len(), 2);
+
+        // Navigation on the saved tab is independent of the news list
+        app.collect(NewsroomTransitions::Down);
+        assert_eq!(app.saved_row, Some(0));
+        assert_eq!(app.selected_article().map(|article| article.link.as_str()), Some("b"));
+        app.collect(NewsroomTransitions::ToggleSaved);
+        assert!(!app.saved.is_saved("b"));
+        assert_eq!(app.selected_article().map(|article| article.link.as_str()), Some("a"));
+    }
+
+    // Test that cycling the sort mode reorders the list without losing the selection
+    #[tokio::test]
+    async fn test_cycle_sort(){
+        let mut app = test_app();
+        app.settings.sort = SortMode::Newest;
+        let a = DataSources::new("a", "http://a.example.com");
+        let b = DataSources::new("b", "http://b.example.com");
+        let dated = |source: &DataSources, link: &str, date: &str| NewsArticle {
+            published: chrono::DateTime::parse_from_rfc3339(date).ok(),
+            ..NewsArticle::test(source, link)
+        };
+        app.newsroom_state = NewsroomState::FetchMedia(vec![a.clone(), b.clone()]);
+        app.collect(NewsroomTransitions::ReturnSourceMedia(b.clone(), vec![dated(&b, "b-new", "2023-10-03T00:00:00Z")]));
+        app.collect(NewsroomTransitions::ReturnSourceMedia(a.clone(), vec![dated(&a, "a-old", "2023-10-01T00:00:00Z")]));
+        let NewsroomState::DisplayMedia(articles) = &app.newsroom_state else { panic!() };
+        assert_eq!(articles[0].link, "b-new");
+
+        app.collect(NewsroomTransitions::Down);
+        app.collect(NewsroomTransitions::CycleSort);
+        assert_eq!(app.settings.sort, SortMode::Grouped);
+        let NewsroomState::DisplayMedia(articles) = &app.newsroom_state else { panic!() };
+        assert_eq!(articles[0].link, "a-old");
+        assert_eq!(app.selected_article().map(|article| article.link.as_str()), Some("b-new"));
+    }
+
+    // Test that searching filters navigation to matches and that cancelling keeps the selection
+    #[tokio::test]
+    async fn test_search(){
+        let mut app = test_app();
+        let source = DataSources::new("source", "http://example.com");
+        let titled = |link: &str, title: &str| NewsArticle { title: title.to_string(), ..NewsArticle::test(&source, link) };
+        app.newsroom_state = NewsroomState::DisplayMedia(vec![
+            titled("a", "Rates hold"),
+            titled("b", "Election night"),
+            titled("c", "Rates rise"),
+            titled("d", "Weather"),
+        ]);
+        app.collect(NewsroomTransitions::Down);
+        app.collect(NewsroomTransitions::Down);
+        assert_eq!(app.row, Some(1));
+
+        app.collect(NewsroomTransitions::StartSearch);
+        for c in "rATes".chars() {
+            app.collect(NewsroomTransitions::SearchInput(c));
+        }
+        // The selected article stopped matching, so the first match is selected
+        assert_eq!(app.row, Some(0));
+        app.collect(NewsroomTransitions::ConfirmSearch);
+        app.collect(NewsroomTransitions::NextMatch);
+        assert_eq!(app.row, Some(2));
+        app.collect(NewsroomTransitions::NextMatch);
+        assert_eq!(app.row, Some(0));
+        app.collect(NewsroomTransitions::PreviousMatch);
+        assert_eq!(app.row, Some(2));
+
+        app.collect(NewsroomTransitions::CancelSearch);
+        assert!(!app.filter.is_active());
+        assert_eq!(app.selected_article().map(|article| article.link.as_str()), Some("c"));
+        app.collect(NewsroomTransitions::NextMatch);
+        assert_eq!(app.row, Some(2));
+        app.collect(NewsroomTransitions::Down);
+        assert_eq!(app.row, Some(3));
+    }
+
+    // Test that Ctrl-C quits while typing into the search line, a form or the command line, where a bare c is typed
+    #[tokio::test]
+    async fn test_quit_from_text_input(){
+        let press = |code: KeyCode, modifiers: KeyModifiers| KeyEvent::new(code, modifiers);
+        let mut app = test_app();
+        app.newsroom_state = NewsroomState::DisplayMedia(vec![]);
+        app.collect(NewsroomTransitions::StartSearch);
+        handle_key_events(press(KeyCode::Char('c'), KeyModifiers::NONE), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert_eq!(app.filter.query, "c");
+        assert!(app.running);
+        handle_key_events(press(KeyCode::Char('c'), KeyModifiers::CONTROL), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert_eq!(app.filter.query, "c");
+        assert!(!app.running);
+
+        let mut app = test_app();
+        app.collect(NewsroomTransitions::ToSettings);
+        app.collect(NewsroomTransitions::OpenForm(FormAction::AddSource));
+        handle_key_events(press(KeyCode::Char('c'), KeyModifiers::NONE), &app).unwrap();
+        // Alt chords aren't typed either
+        handle_key_events(press(KeyCode::Char('x'), KeyModifiers::ALT), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert_eq!(app.form.as_ref().map(|form| form.value(0)), Some("c"));
+        handle_key_events(press(KeyCode::Char('c'), KeyModifiers::CONTROL), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert!(!app.running);
+
+        let mut app = test_app();
+        app.collect(NewsroomTransitions::OpenCommand);
+        handle_key_events(press(KeyCode::Char('q'), KeyModifiers::NONE), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert_eq!(app.command.as_ref().map(|command| command.input.as_str()), Some("q"));
+        handle_key_events(press(KeyCode::Char('c'), KeyModifiers::CONTROL), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert!(!app.running);
+    }
+
+    // Test that forms, the command line and the theme picker take their keys from the keymap
+    #[tokio::test]
+    async fn test_rebound_keys(){
+        let press = |code: KeyCode, modifiers: KeyModifiers| KeyEvent::new(code, modifiers);
+        let keys = toml::from_str(r#"
+            cancel_form = "ctrl-g"
+            next_field = "ctrl-n"
+            submit_command = "ctrl-s"
+            cancel_theme = "x"
+        "#).unwrap();
+        let (keymap, problems) = Keymap::load(&keys);
+        assert!(problems.is_empty(), "{:?}", problems);
+        let mut app = test_app();
+        app.settings.keymap = keymap;
+
+        app.collect(NewsroomTransitions::ToSettings);
+        app.collect(NewsroomTransitions::OpenForm(FormAction::AddSource));
+        handle_key_events(press(KeyCode::Esc, KeyModifiers::NONE), &app).unwrap();
+        handle_key_events(press(KeyCode::Tab, KeyModifiers::NONE), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert_eq!(app.form.as_ref().map(|form| form.focus), Some(0));
+        handle_key_events(press(KeyCode::Char('n'), KeyModifiers::CONTROL), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert_eq!(app.form.as_ref().map(|form| form.focus), Some(1));
+        handle_key_events(press(KeyCode::Char('g'), KeyModifiers::CONTROL), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert!(app.form.is_none());
+
+        app.collect(NewsroomTransitions::OpenCommand);
+        handle_key_events(press(KeyCode::Enter, KeyModifiers::NONE), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert!(app.command.is_some());
+        handle_key_events(press(KeyCode::Char('s'), KeyModifiers::CONTROL), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert!(app.command.is_none());
+
+        app.collect(NewsroomTransitions::OpenThemePicker);
+        app.collect(NewsroomTransitions::Down);
+        handle_key_events(press(KeyCode::Esc, KeyModifiers::NONE), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert!(app.theme_picker.is_some());
+        handle_key_events(press(KeyCode::Char('x'), KeyModifiers::NONE), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert!(app.theme_picker.is_none());
+        assert_eq!(app.settings.theme.name, "default");
+    }
+
+    // Test that the sidebar takes the arrow keys while focused and filters the list by source
+    #[tokio::test]
+    async fn test_sidebar(){
+        let mut app = test_app();
+        let a = DataSources::new("a", "http://a.example.com");
+        let b = DataSources::new("b", "http://b.example.com");
+        app.settings.sources = vec![a.clone(), b.clone()];
+        app.newsroom_state = NewsroomState::DisplayMedia(vec![NewsArticle::test(&a, "a1"), NewsArticle::test(&b, "b1"), NewsArticle::test(&a, "a2")]);
+        app.collect(NewsroomTransitions::Down);
+        assert_eq!(app.row, Some(0));
+
+        app.collect(NewsroomTransitions::Left);
+        assert!(app.sidebar_open && app.sidebar_focused);
+        app.collect(NewsroomTransitions::Down);
+        app.collect(NewsroomTransitions::Down);
+        assert_eq!(app.sidebar_row, 2);
+        // Only b's article is left, so it gets selected
+        assert_eq!(app.selected_article().map(|article| article.link.as_str()), Some("b1"));
+
+        app.collect(NewsroomTransitions::Right);
+        assert!(app.sidebar_open && !app.sidebar_focused);
+        app.collect(NewsroomTransitions::Down);
+        assert_eq!(app.row, Some(1));
+
+        // Back to every source, wrapping around from the top
+        app.collect(NewsroomTransitions::Left);
+        app.collect(NewsroomTransitions::Down);
+        assert_eq!(app.sidebar_row, 0);
+        assert_eq!(app.filter.source_url, None);
+        app.collect(NewsroomTransitions::ToggleSidebar);
+        assert!(!app.sidebar_open && !app.sidebar_focused);
+
+        // Closing the sidebar drops the filter it was showing
+        app.collect(NewsroomTransitions::Left);
+        app.collect(NewsroomTransitions::Down);
+        assert_eq!(app.filter.source_url.as_deref(), Some("http://a.example.com"));
+        app.collect(NewsroomTransitions::ToggleSidebar);
+        assert_eq!(app.filter.source_url, None);
+        assert_eq!(app.sidebar_row, 0);
+    }
+
+    // Test that leaving the news tab with the sidebar focused hands enter and the arrows back to the saved tab
+    #[tokio::test]
+    async fn test_sidebar_other_tabs(){
+        let press = |code: KeyCode| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut app = test_app();
+        let a = DataSources::new("a", "http://a.example.com");
+        app.settings.sources = vec![a.clone()];
+        app.newsroom_state = NewsroomState::DisplayMedia(vec![NewsArticle::test(&a, "a1"), NewsArticle::test(&a, "a2")]);
+        app.collect(NewsroomTransitions::Down);
+        app.collect(NewsroomTransitions::Down);
+        app.collect(NewsroomTransitions::ToggleSaved);
+        app.collect(NewsroomTransitions::Left);
+        assert!(app.sidebar_focused);
+
+        handle_key_events(press(KeyCode::Tab), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert_eq!(app.tab, 1);
+        assert!(!app.sidebar_focused);
+        // The sidebar can't be toggled from the saved tab either
+        app.collect(NewsroomTransitions::ToggleSidebar);
+        assert!(app.sidebar_open && !app.sidebar_focused);
+        handle_key_events(press(KeyCode::Down), &app).unwrap();
+        handle_key_events(press(KeyCode::Enter), &app).unwrap();
+        while app.poll_and_run_action().await {}
+        assert_eq!(app.saved_row, Some(0));
+        assert_eq!(app.reader.as_ref().map(|reader| reader.article.link.as_str()), Some("a2"));
+    }
+
+    #[tokio::test]
+    async fn test_reader(){
+        let mut app = test_app();
+        let source = DataSources::new("source", "http://example.com");
+        let mut article = NewsArticle::test(&source, "a");
+        article.content = Some((1..=30).map(|i| format!("<p>Paragraph {}</p>", i)).collect());
+        app.newsroom_state = NewsroomState::DisplayMedia(vec![article]);
+        app.resize(80, 24);
+
+        // Nothing is selected yet, so there is nothing to read
+        app.collect(NewsroomTransitions::OpenReader);
+        assert!(app.reader.is_none());
+
+        app.collect(NewsroomTransitions::Down);
+        app.collect(NewsroomTransitions::OpenReader);
+        assert!(app.read.is_read("a"));
+        assert_eq!(app.browser_article().map(|article| article.link.as_str()), Some("a"));
+        // A refresh which drops the article underneath the reader moves the selection, but not what is being read
+        app.collect(NewsroomTransitions::ReturnSourceMedia(source.clone(), vec![NewsArticle::test(&source, "b")]));
+        assert_eq!(app.selected_article().map(|article| article.link.as_str()), Some("b"));
+        assert_eq!(app.browser_article().map(|article| article.link.as_str()), Some("a"));
+        app.collect(NewsroomTransitions::ScrollReader(5));
+        app.collect(NewsroomTransitions::ScrollReader(-2));
+        assert_eq!(app.reader.as_ref().map(|reader| reader.scroll), Some(3));
+
+        // Growing the terminal pulls the scroll back so the end of the article stays at the bottom
+        app.collect(NewsroomTransitions::ScrollReader(i32::MAX));
+        let bottom = app.reader.as_ref().unwrap().scroll;
+        app.resize(80, 40);
+        assert_eq!(app.reader.as_ref().map(|reader| reader.scroll), Some(bottom - 16));
+
+        app.collect(NewsroomTransitions::CloseReader);
+        assert!(app.reader.is_none());
+        assert_eq!(app.row, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_full_text_reader(){
+        let server = StubServer::start(vec![
+            ("/story", vec![StubResponse::ok(include_str!("../resources/fixtures/article.html"))]),
+        ]).await;
+        let mut app = test_app();
+        let mut source = DataSources::new("teasers", "http://example.com/feed");
+        app.settings.sources = vec![source.clone()];
+        let mut article = NewsArticle::test(&source, &server.url("/story"));
+        article.summary = "Council approves bike lanes".to_string();
+        app.newsroom_state = NewsroomState::DisplayMedia(vec![article]);
+        app.collect(NewsroomTransitions::Down);
+
+        // Sources only fetch the full story when asked to
+        app.collect(NewsroomTransitions::OpenReader);
+        assert_eq!(app.reader.as_ref().map(|reader| &reader.full_text), Some(&FullText::Skipped));
+        app.collect(NewsroomTransitions::CloseReader);
+
+        source.full_text = true;
+        app.settings.sources = vec![source];
+        app.collect(NewsroomTransitions::OpenReader);
+        assert_eq!(app.reader.as_ref().map(|reader| &reader.full_text), Some(&FullText::Loading));
+        while app.reader.as_ref().is_some_and(|reader| reader.full_text == FullText::Loading) {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            app.poll_and_run_action().await;
+        }
+        let reader = app.reader.as_ref().unwrap();
+        assert_eq!(reader.full_text, FullText::Loaded);
+        assert!(reader.text.lines.iter().any(|line| line.spans.iter().any(|span| span.content.contains("The council voted on Tuesday"))));
+    }
+
+    #[tokio::test]
+    async fn test_opml_forms(){
+        let mut app = test_app();
+        let file = TempPath::new("sources.opml");
+        let sources = app.settings.sources.len();
+
+        // Forms only open on the settings tab
+        app.collect(NewsroomTransitions::OpenForm(FormAction::ExportOpml));
+        assert!(app.form.is_none());
+        app.collect(NewsroomTransitions::ToSettings);
+        app.collect(NewsroomTransitions::OpenForm(FormAction::ExportOpml));
+        app.form.as_mut().unwrap().fields[0].value = file.display().to_string();
+        app.collect(NewsroomTransitions::SubmitForm);
+        assert!(app.form.is_none());
+        assert!(file.exists());
+
+        // A bad path keeps the form open with the error
+        app.collect(NewsroomTransitions::OpenForm(FormAction::ImportOpml));
+        app.form.as_mut().unwrap().fields[0].value = file.display().to_string();
+        app.collect(NewsroomTransitions::FormInput('x'));
+        app.collect(NewsroomTransitions::SubmitForm);
+        assert!(app.form.as_ref().is_some_and(|form| form.error.is_some()));
+        app.collect(NewsroomTransitions::FormBackspace);
+        app.collect(NewsroomTransitions::SubmitForm);
+        assert!(app.form.is_none());
+        assert_eq!(app.settings_message.as_deref().map(|message| message.starts_with("Imported 0 new sources")), Some(true));
+        assert_eq!(app.settings.sources.len(), sources);
+    }
+
+    #[test]
+    fn test_theme_picker(){
+        let mut app = test_app();
+        app.settings.theme = Theme::new();
+        app.settings.custom_themes = toml::from_str("[mine]\nbase = \"light\"").unwrap();
+        app.collect(NewsroomTransitions::ToSettings);
+        app.collect(NewsroomTransitions::OpenThemePicker);
+        assert_eq!(app.theme_picker.as_ref().map(|picker| picker.names.len()), Some(6));
+
+        // Moving previews the theme, cancelling puts the old one back
+        app.collect(NewsroomTransitions::Down);
+        assert_eq!(app.settings.theme.name, "light");
+        assert_eq!(app.settings_row, None);
+        app.collect(NewsroomTransitions::CancelThemePicker);
+        assert!(app.theme_picker.is_none());
+        assert_eq!(app.settings.theme.name, "default");
+
+        app.collect(NewsroomTransitions::OpenThemePicker);
+        app.collect(NewsroomTransitions::Up);
+        assert_eq!(app.settings.theme.name, "mine");
+        app.collect(NewsroomTransitions::ConfirmTheme);
+        assert!(app.theme_picker.is_none());
+        assert_eq!(app.settings.theme.name, "mine");
+        assert_eq!(app.settings_message.as_deref(), Some("Switched to the mine theme"));
+    }
+
+    /// Runs transitions until the open form has heard back about the url it is checking
+    async fn wait_for_check(app: &mut App) {
+        while app.form.as_ref().is_some_and(|form| form.checking.is_some()) {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            app.poll_and_run_action().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_edit_sources(){
+        let server = StubServer::start(vec![
+            ("/two.rss", vec![StubResponse::ok(include_str!("../resources/fixtures/rss.xml"))]),
+        ]).await;
+        let mut app = test_app();
+        app.settings.sources = vec![DataSources::new("one", "https://example.com/one.rss")];
+        app.collect(NewsroomTransitions::ToSettings);
+
+        // Invalid sources are rejected with the reason
+        app.collect(NewsroomTransitions::OpenForm(FormAction::AddSource));
+        "two".chars().for_each(|c| app.collect(NewsroomTransitions::FormInput(c)));
+        app.collect(NewsroomTransitions::NextField);
+        "example.com/two.rss".chars().for_each(|c| app.collect(NewsroomTransitions::FormInput(c)));
+        app.collect(NewsroomTransitions::SubmitForm);
+        assert!(app.form.as_ref().is_some_and(|form| form.error.is_some()));
+        app.form.as_mut().unwrap().fields[1].value = "https://example.com/one.rss".to_string();
+        app.collect(NewsroomTransitions::SubmitForm);
+        assert!(app.form.as_ref().is_some_and(|form| form.error.as_deref() == Some("https://example.com/one.rss is already a source")));
+
+        app.form.as_mut().unwrap().fields[1].value = server.url("/two.rss");
+        app.form.as_mut().unwrap().fields[2].value = "/Tech/".to_string();
+        app.collect(NewsroomTransitions::SubmitForm);
+        wait_for_check(&mut app).await;
+        assert!(app.form.is_none());
+        assert_eq!(app.settings.sources[1].category.as_deref(), Some("Tech"));
+        assert_eq!(app.settings_row, Some(1));
+
+        // Editing keeps the settings the form doesn't show
+        app.settings.sources[1].retries = Some(5);
+        app.collect(NewsroomTransitions::OpenForm(FormAction::EditSource(1)));
+        app.collect(NewsroomTransitions::FormInput('!'));
+        app.collect(NewsroomTransitions::SubmitForm);
+        assert_eq!(app.settings.sources[1].name, "two!");
+        assert_eq!(app.settings.sources[1].retries, Some(5));
+
+        app.collect(NewsroomTransitions::MoveSourceUp);
+        app.collect(NewsroomTransitions::ToggleSource);
+        assert_eq!(app.settings.sources.iter().map(|source| source.name.as_str()).collect_vec(), vec!["two!", "one"]);
+        assert_eq!(app.settings.enabled_sources().iter().map(|source| source.name.as_str()).collect_vec(), vec!["one"]);
+
+        app.collect(NewsroomTransitions::OpenForm(FormAction::DeleteSource(0)));
+        app.collect(NewsroomTransitions::SubmitForm);
+        assert_eq!(app.settings.sources.iter().map(|source| source.name.as_str()).collect_vec(), vec!["one"]);
+        assert_eq!(app.settings_row, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_discover_sources(){
+        let homepage = r#"<html><head>
+            <link rel="alternate" type="application/rss+xml" title="Top stories" href="/feed.rss">
+            <link rel="alternate" type="application/atom+xml" href="/missing.atom">
+        </head></html>"#;
+        let server = StubServer::start(vec![
+            ("/", vec![StubResponse::ok(homepage).header("Content-Type", "text/html")]),
+            ("/feed.rss", vec![StubResponse::ok(include_str!("../resources/fixtures/rss.xml"))]),
+            ("/plain", vec![StubResponse::ok("Just some text").header("Content-Type", "text/plain")]),
+            ("/empty", vec![StubResponse::ok("<html><body>Nothing here</body></html>").header("Content-Type", "text/html")]),
+        ]).await;
+        let mut app = test_app();
+        app.settings.sources = vec![];
+        app.collect(NewsroomTransitions::ToSettings);
+        app.collect(NewsroomTransitions::OpenForm(FormAction::AddSource));
+
+        // Urls which aren't feeds, or don't lead to any, are rejected with the reason
+        for (path, error) in [("/plain", "not an RSS, Atom or JSON feed"), ("/empty", "No feeds were found on that page"), ("/nowhere", "HTTP 404 Not Found")] {
+            app.form.as_mut().unwrap().fields[1].value = server.url(path);
+            app.collect(NewsroomTransitions::SubmitForm);
+            assert_eq!(app.form.as_ref().and_then(|form| form.checking.clone()), Some(server.url(path)));
+            wait_for_check(&mut app).await;
+            assert_eq!(app.form.as_ref().and_then(|form| form.error.as_deref()), Some(error));
+        }
+
+        // A web page offers the feeds it links to, and the chosen feed is checked too
+        app.form.as_mut().unwrap().fields[1].value = server.url("/");
+        app.collect(NewsroomTransitions::SubmitForm);
+        wait_for_check(&mut app).await;
+        let form = app.form.as_ref().unwrap();
+        assert!(matches!(form.action, FormAction::ChooseFeed { .. }));
+        assert_eq!(form.options, vec![format!("Top stories  {}", server.url("/feed.rss")), server.url("/missing.atom")]);
+        app.collect(NewsroomTransitions::NextField);
+        app.collect(NewsroomTransitions::SubmitForm);
+        wait_for_check(&mut app).await;
+        assert_eq!(app.form.as_ref().and_then(|form| form.error.as_deref()), Some("HTTP 404 Not Found"));
+        app.collect(NewsroomTransitions::PreviousField);
+        app.collect(NewsroomTransitions::SubmitForm);
+        wait_for_check(&mut app).await;
+        assert!(app.form.is_none());
+
+        // The name was left empty, so it comes from the feed
+        assert_eq!(app.settings.sources.len(), 1);
+        assert_eq!(app.settings.sources[0].name, "Newsroom Test Channel");
+        assert_eq!(app.settings.sources[0].url, server.url("/feed.rss"));
+    }
+
+    /// Types a command into the command line and runs it, along with the transitions it stands for
+    async fn run_command(app: &mut App, command: &str) {
+        app.collect(NewsroomTransitions::OpenCommand);
+        for c in command.chars() {
+            app.collect(NewsroomTransitions::CommandInput(c));
+        }
+        app.collect(NewsroomTransitions::SubmitCommand);
+        while app.poll_and_run_action().await {}
+    }
+
+    #[tokio::test]
+    async fn test_command_line(){
+        let mut app = test_app();
+        let cbc = DataSources::new("cbc", "http://cbc.example.com");
+        let cnn = DataSources::new("cnn", "http://cnn.example.com");
+        app.settings.sources = vec![cbc.clone(), cnn.clone()];
+        app.newsroom_state = NewsroomState::DisplayMedia(vec![NewsArticle::test(&cnn, "a"), NewsArticle::test(&cbc, "b"), NewsArticle::test(&cbc, "c")]);
+
+        // Completing fills in the rest of the word
+        app.collect(NewsroomTransitions::OpenCommand);
+        for c in "sort gr".chars() {
+            app.collect(NewsroomTransitions::CommandInput(c));
+        }
+        app.collect(NewsroomTransitions::CompleteCommand);
+        assert_eq!(app.command.as_ref().map(|command| command.input.as_str()), Some("sort grouped "));
+        app.collect(NewsroomTransitions::SubmitCommand);
+        while app.poll_and_run_action().await {}
+        assert!(app.command.is_none());
+        assert_eq!(app.settings.sort, SortMode::Grouped);
+
+        run_command(&mut app, "filter CBC").await;
+        assert_eq!(app.filter.source_url.as_deref(), Some("http://cbc.example.com"));
+        assert_eq!(app.sidebar_row, 1);
+        // Articles are counted in the list as it is shown
+        run_command(&mut app, "open 2").await;
+        assert_eq!(app.reader.as_ref().map(|reader| reader.article.link.as_str()), Some("c"));
+        app.collect(NewsroomTransitions::CloseReader);
+        run_command(&mut app, "open 3").await;
+        assert!(app.reader.is_none());
+        assert_eq!(app.status.as_ref().map(|(status, _)| status.as_str()), Some("There is no article 3 in the list"));
+
+        let file = TempPath::new("digest.md");
+        run_command(&mut app, &format!("export md {}", file.display())).await;
+        let digest = fs::read_to_string(&file).unwrap();
+        assert!(digest.starts_with("# Newsroom digest, "));
+        assert!(digest.contains("## cbc\n\n- [b](b)\n- [c](c)\n") && !digest.contains("## cnn"));
+        assert_eq!(app.status.as_ref().map(|(status, _)| status.clone()), Some(format!("Exported 2 articles to {}", file.display())));
+
+        run_command(&mut app, "theme light").await;
+        assert_eq!(app.settings.theme.name, "light");
+        assert_eq!(app.status.as_ref().map(|(status, _)| status.as_str()), Some("Switched to the light theme"));
+
+        // Mistakes keep the command line open with the reason
+        run_command(&mut app, "sort sideways").await;
+        assert_eq!(app.settings.sort, SortMode::Grouped);
+        assert_eq!(app.command.as_ref().and_then(|command| command.error.as_deref()), Some("Sort by newest, grouped, round-robin or shuffled"));
+        app.collect(NewsroomTransitions::CancelCommand);
+        assert!(app.command.is_none());
+
+        // Sources are deleted through the settings tab's form, which asks first
+        run_command(&mut app, "source delete cnn").await;
+        assert_eq!(app.tab, 2);
+        // Searching from there would type into a search line that isn't shown
+        app.collect(NewsroomTransitions::CancelForm);
+        run_command(&mut app, "search rates").await;
+        assert!(app.filter.query.is_empty() && !app.filter.editing);
+        assert_eq!(app.command.as_ref().and_then(|command| command.error.as_deref()), Some("Searching only works on the News tab"));
+        app.collect(NewsroomTransitions::CancelCommand);
+        run_command(&mut app, "source delete cnn").await;
+        assert_eq!(app.form.as_ref().map(|form| form.action.clone()), Some(FormAction::DeleteSource(1)));
+        app.collect(NewsroomTransitions::SubmitForm);
+        assert_eq!(app.settings.sources, vec![cbc]);
+        // Opening the settings tab again leaves the articles held underneath it alone
+        app.collect(NewsroomTransitions::ToSettings);
+        assert!(matches!(app.newsroom_state, NewsroomState::ManageSettings(Some(_))));
+    }
 }
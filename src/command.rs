@@ -0,0 +1,341 @@
+use itertools::Itertools;
+
+use crate::{
+    newsroomcore::{datasources::DataSources, formaction::FormAction, newsroomstate::NewsroomTransitions, sortmode::SortMode},
+    settings::{Settings, expand_home},
+};
+
+/// Every command: its name, how it is written and what it does
+pub const COMMANDS: [(&str, &str, &str); 13] = [
+    ("refresh", "refresh", "Fetch the latest articles"),
+    ("source", "source add <url> [name]", "Add a source, checking its feed first"),
+    ("source", "source delete <name>", "Delete a source"),
+    ("sort", "sort <mode>", "Change how articles are sorted"),
+    ("theme", "theme <name>", "Switch to another theme"),
+    ("filter", "filter [source]", "Show one source's articles, or every one"),
+    ("search", "search <text>", "Search the headlines"),
+    ("open", "open <number>", "Read the nth article in the list"),
+    ("import", "import [file]", "Import sources from OPML"),
+    ("export", "export md <file>", "Write the listed articles as markdown"),
+    ("export", "export opml [file]", "Export sources to OPML"),
+    ("help", "help", "Show every key and command"),
+    ("quit", "quit", "Quit newsroom"),
+];
+
+/// The command being typed after ':', along with the completions offered for it
+#[derive(Debug, Clone, Default)]
+pub struct CommandLine {
+    pub input: String,
+    /// Why the last command couldn't be run, shown until the command is changed
+    pub error: Option<String>,
+    /// What the word being typed could be, offered when completing couldn't settle on one
+    pub completions: Vec<String>,
+    /// The completion filled in, completing again moves on to the next one
+    completion: Option<usize>,
+    /// Where in the input the completed word starts
+    completion_start: usize,
+}
+
+impl CommandLine {
+    pub fn input(&mut self, c: char) {
+        self.input.push(c);
+        self.edited();
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+        self.edited();
+    }
+
+    /// Completes the word being typed. A single match is filled in, several are listed after filling in
+    /// what they have in common, and completing again cycles through them
+    ///
+    /// Arguments
+    /// * settings - The settings to complete source and theme names from
+    pub fn complete(&mut self, settings: &Settings) {
+        self.error = None;
+        if !self.completions.is_empty() {
+            let next = self.completion.map_or(0, |index| (index + 1) % self.completions.len());
+            self.completion = Some(next);
+            self.input.truncate(self.completion_start);
+            self.input.push_str(&self.completions[next]);
+            return;
+        }
+        let (start, candidates) = completions(&self.input, settings);
+        match candidates.as_slice() {
+            [] => {},
+            [only] => {
+                self.input.truncate(start);
+                self.input.push_str(only);
+                self.input.push(' ');
+            },
+            _ => {
+                let shared = common_prefix(&candidates);
+                // Matching ignores case, so keep what was typed unless the candidates agree on more
+                if shared.chars().count() > self.input[start..].chars().count() {
+                    self.input.truncate(start);
+                    self.input.push_str(&shared);
+                }
+                self.completions = candidates;
+                self.completion_start = start;
+            },
+        }
+    }
+
+    /// Forgets the completions and the error, which no longer apply once the command changes
+    fn edited(&mut self) {
+        self.error = None;
+        self.completions.clear();
+        self.completion = None;
+    }
+}
+
+/// Works out the transitions which carry a command out
+///
+/// Arguments
+/// * input - The command, with or without the leading ':'
+/// * settings - The settings to look sources and themes up in
+pub fn parse(input: &str, settings: &Settings) -> Result<Vec<NewsroomTransitions>, String> {
+    let input = input.trim().trim_start_matches(':').trim_start();
+    let (command, rest) = split_word(input);
+    Ok(match (command, rest) {
+        ("", _) => vec![],
+        ("refresh", "") => vec![NewsroomTransitions::FetchMedia(settings.enabled_sources())],
+        ("help", "") => vec![NewsroomTransitions::ToggleHelp],
+        ("quit", "") => vec![NewsroomTransitions::Quit],
+        ("sort", mode) => match SortMode::from_name(mode) {
+            Some(mode) => vec![NewsroomTransitions::SetSort(mode)],
+            None => {
+                let modes = SortMode::ALL.iter().map(|mode| mode.name()).collect_vec();
+                return Err(format!("Sort by {} or {}", modes[..modes.len() - 1].join(", "), modes[modes.len() - 1]));
+            },
+        },
+        ("theme", "") => return Err(usage(command)),
+        ("theme", name) if settings.theme_names().iter().any(|theme| theme == name) => vec![NewsroomTransitions::SetTheme(name.to_string())],
+        ("theme", name) => return Err(format!("There is no theme called {}", name)),
+        ("filter", "") => vec![NewsroomTransitions::FilterSource(None)],
+        ("filter", name) => {
+            let sources = settings.enabled_sources();
+            let index = find_source(&sources, name)?;
+            vec![NewsroomTransitions::FilterSource(Some(sources[index].url.clone()))]
+        },
+        ("search", "") => return Err(usage(command)),
+        // Typed into the search line, as if the query had been entered after pressing /
+        ("search", query) => [NewsroomTransitions::StartSearch]
+            .into_iter()
+            .chain(query.chars().map(NewsroomTransitions::SearchInput))
+            .chain([NewsroomTransitions::ConfirmSearch])
+            .collect(),
+        ("open", number) => match number.parse::<usize>() {
+            Ok(number) if number > 0 => vec![NewsroomTransitions::OpenArticle(number - 1)],
+            _ => return Err(usage(command)),
+        },
+        // Sources are added and deleted through the same forms as in the settings tab
+        ("source", rest) => match split_word(rest) {
+            ("add", "") => return Err(usage(command)),
+            ("add", args) => {
+                let (url, name) = split_word(args);
+                let mut transitions = vec![NewsroomTransitions::ToSettings, NewsroomTransitions::OpenForm(FormAction::AddSource)];
+                if !name.is_empty() {
+                    transitions.push(NewsroomTransitions::FormSet(name.to_string()));
+                }
+                transitions.extend([
+                    NewsroomTransitions::NextField,
+                    NewsroomTransitions::FormSet(url.to_string()),
+                    NewsroomTransitions::SubmitForm,
+                ]);
+                transitions
+            },
+            ("delete", name) if !name.is_empty() => {
+                let index = find_source(&settings.sources, name)?;
+                vec![NewsroomTransitions::ToSettings, NewsroomTransitions::OpenForm(FormAction::DeleteSource(index))]
+            },
+            _ => return Err(usage(command)),
+        },
+        ("import", file) => file_form(FormAction::ImportOpml, file),
+        ("export", rest) => match split_word(rest) {
+            ("md" | "markdown", "") => return Err(usage(command)),
+            ("md" | "markdown", file) => vec![NewsroomTransitions::ExportDigest(expand_home(file))],
+            ("opml", file) => file_form(FormAction::ExportOpml, file),
+            _ => return Err(usage(command)),
+        },
+        (command, _) if COMMANDS.iter().any(|(name, _, _)| *name == command) => return Err(usage(command)),
+        (command, _) => return Err(format!("Unknown command {}, :help lists every command", command)),
+    })
+}
+
+/// What the word at the end of a command could be completed to, along with where in the command that word starts.
+/// Names of sources may have spaces in them, so they are completed as a whole
+///
+/// Arguments
+/// * input - The command typed so far, without the leading ':'
+/// * settings - The settings to complete source and theme names from
+pub fn completions(input: &str, settings: &Settings) -> (usize, Vec<String>) {
+    let start_of = |text: &str| input.len() - text.len();
+    let command = input.trim_start();
+    let (word, options): (&str, Vec<String>) = match command.split_once(' ') {
+        None => (command, COMMANDS.iter().map(|(name, _, _)| name.to_string()).unique().collect()),
+        Some((command, rest)) => {
+            let rest = rest.trim_start();
+            match (command, rest.split_once(' ')) {
+                ("source", None) => (rest, vec!["add".to_string(), "delete".to_string()]),
+                ("source", Some(("delete", name))) => (name.trim_start(), settings.sources.iter().map(|source| source.name.clone()).collect()),
+                ("export", None) => (rest, vec!["md".to_string(), "opml".to_string()]),
+                ("sort", _) => (rest, SortMode::ALL.iter().map(|mode| mode.name().to_string()).collect()),
+                ("theme", _) => (rest, settings.theme_names()),
+                ("filter", _) => (rest, settings.enabled_sources().into_iter().map(|source| source.name).collect()),
+                _ => return (input.len(), vec![]),
+            }
+        },
+    };
+    let typed = word.to_lowercase();
+    let candidates = options.into_iter().filter(|option| option.to_lowercase().starts_with(&typed)).collect();
+    (start_of(word), candidates)
+}
+
+/// Splits off the first word, returning it and the rest without surrounding whitespace
+fn split_word(text: &str) -> (&str, &str) {
+    match text.trim().split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (text.trim(), ""),
+    }
+}
+
+/// How a command is written, for when it was written wrong
+fn usage(command: &str) -> String {
+    let usages = COMMANDS.iter().filter(|(name, _, _)| *name == command).map(|(_, usage, _)| format!(":{}", usage));
+    format!("Usage: {}", usages.format(" or "))
+}
+
+/// Finds a source by its name, ignoring case
+///
+/// Arguments
+/// * sources - The sources to look in
+/// * name - The name of the source
+fn find_source(sources: &[DataSources], name: &str) -> Result<usize, String> {
+    sources
+        .iter()
+        .position(|source| source.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("There is no source called {}", name))
+}
+
+/// Opens one of the OPML forms in the settings tab and submits it, with the file given or the form's default
+///
+/// Arguments
+/// * action - Which OPML form to use
+/// * file - The file to use, empty to use the form's default
+fn file_form(action: FormAction, file: &str) -> Vec<NewsroomTransitions> {
+    let mut transitions = vec![NewsroomTransitions::ToSettings, NewsroomTransitions::OpenForm(action)];
+    if !file.is_empty() {
+        transitions.push(NewsroomTransitions::FormSet(file.to_string()));
+    }
+    transitions.push(NewsroomTransitions::SubmitForm);
+    transitions
+}
+
+/// The longest start that every candidate shares
+fn common_prefix(candidates: &[String]) -> String {
+    let Some((first, rest)) = candidates.split_first() else { return String::new() };
+    first
+        .chars()
+        .enumerate()
+        .take_while(|(index, c)| rest.iter().all(|candidate| candidate.chars().nth(*index) == Some(*c)))
+        .map(|(_, c)| c)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_settings() -> Settings {
+        let mut disabled = DataSources::new("cnn world", "https://cnn.com/world.rss");
+        disabled.disabled = true;
+        Settings::test(vec![
+            DataSources::new("CBC", "https://cbc.ca/rss"),
+            DataSources::new("cnn", "https://cnn.com/rss"),
+            DataSources::new("globe and mail", "https://theglobeandmail.com/rss"),
+            disabled,
+        ])
+    }
+
+    #[test]
+    fn test_parse_commands(){
+        let settings = test_settings();
+        assert!(matches!(parse(":sort date", &settings).unwrap()[..], [NewsroomTransitions::SetSort(SortMode::Newest)]));
+        assert!(matches!(parse("sort round-robin", &settings).unwrap()[..], [NewsroomTransitions::SetSort(SortMode::RoundRobin)]));
+        assert!(matches!(&parse(":theme solarized", &settings).unwrap()[..], [NewsroomTransitions::SetTheme(name)] if name == "solarized"));
+        assert!(matches!(&parse(":filter cbc", &settings).unwrap()[..], [NewsroomTransitions::FilterSource(Some(url))] if url == "https://cbc.ca/rss"));
+        assert!(matches!(parse(":filter", &settings).unwrap()[..], [NewsroomTransitions::FilterSource(None)]));
+        assert!(matches!(parse(":open 12", &settings).unwrap()[..], [NewsroomTransitions::OpenArticle(11)]));
+        assert!(matches!(&parse(":refresh", &settings).unwrap()[..], [NewsroomTransitions::FetchMedia(sources)] if sources.len() == 3));
+        assert!(matches!(&parse(":export md ~/digest.md", &settings).unwrap()[..], [NewsroomTransitions::ExportDigest(file)] if *file == expand_home("~/digest.md")));
+        assert!(parse("  ", &settings).unwrap().is_empty());
+
+        // Adding goes through the settings tab's form, so the feed is checked like any other
+        assert!(matches!(
+            &parse(":source add https://example.com/feed.xml World news", &settings).unwrap()[..],
+            [
+                NewsroomTransitions::ToSettings,
+                NewsroomTransitions::OpenForm(FormAction::AddSource),
+                NewsroomTransitions::FormSet(name),
+                NewsroomTransitions::NextField,
+                NewsroomTransitions::FormSet(url),
+                NewsroomTransitions::SubmitForm,
+            ] if name == "World news" && url == "https://example.com/feed.xml"
+        ));
+        assert!(matches!(
+            parse(":source delete CNN WORLD", &settings).unwrap()[..],
+            [NewsroomTransitions::ToSettings, NewsroomTransitions::OpenForm(FormAction::DeleteSource(3))]
+        ));
+        let search = parse(":search rates", &settings).unwrap();
+        assert_eq!(search.len(), 7);
+        assert!(matches!(search[..], [NewsroomTransitions::StartSearch, NewsroomTransitions::SearchInput('r'), .., NewsroomTransitions::ConfirmSearch]));
+
+        let error = |input: &str| parse(input, &settings).err().unwrap();
+        assert_eq!(error(":open 0"), "Usage: :open <number>");
+        assert_eq!(error(":export pdf out.pdf"), "Usage: :export md <file> or :export opml [file]");
+        assert_eq!(error(":refresh now"), "Usage: :refresh");
+        assert_eq!(error(":theme plaid"), "There is no theme called plaid");
+        // Disabled sources have no articles to filter to
+        assert_eq!(error(":filter cnn world"), "There is no source called cnn world");
+        assert_eq!(error(":frobnicate"), "Unknown command frobnicate, :help lists every command");
+    }
+
+    #[test]
+    fn test_complete_commands(){
+        let settings = test_settings();
+        let complete = |input: &str| {
+            let mut command = CommandLine { input: input.to_string(), ..CommandLine::default() };
+            command.complete(&settings);
+            command
+        };
+        assert_eq!(complete("ref").input, "refresh ");
+        assert_eq!(complete("theme so").input, "theme solarized ");
+        assert_eq!(complete("filter GLO").input, "filter globe and mail ");
+        assert_eq!(complete("export ").completions, ["md", "opml"]);
+        assert!(complete("refresh ").completions.is_empty());
+
+        // Several matches fill in what they share, then completing again cycles through them
+        let mut command = complete("source delete c");
+        assert_eq!(command.input, "source delete c");
+        assert_eq!(command.completions, ["CBC", "cnn", "cnn world"]);
+        command.complete(&settings);
+        assert_eq!(command.input, "source delete CBC");
+        command.complete(&settings);
+        command.complete(&settings);
+        command.complete(&settings);
+        assert_eq!(command.input, "source delete CBC");
+
+        // Typing narrows the matches down again
+        let mut command = complete("s");
+        assert_eq!(command.completions, ["source", "sort", "search"]);
+        command.input('o');
+        assert!(command.completions.is_empty());
+        command.complete(&settings);
+        assert_eq!(command.completions, ["source", "sort"]);
+        command.input('u');
+        command.complete(&settings);
+        assert_eq!(command.input, "source ");
+    }
+}
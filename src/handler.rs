@@ -1,39 +1,218 @@
 
 
 use crate::app::{App, AppResult};
-use crate::newsroomcore::newsroomstate::NewsroomTransitions;
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::keymap::{Action, Scope};
+use crate::newsroomcore::{formaction::FormAction, newsroomstate::NewsroomTransitions};
+use crate::tabs::{Help, Reader};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use log::info;
 
 
 /// Handles the key events and updates the state of [`App`].
 pub fn handle_key_events(key_event: KeyEvent, app: &App) -> AppResult<()> {
     info!("user input: {:#?}", key_event);
-    match key_event.code {
-        // Exit application on `ESC` or `q`
-        KeyCode::Esc | KeyCode::Char('q') => {
-            let _ = app.tx.send(NewsroomTransitions::Quit);
-        },
-        // Exit application on `Ctrl-C`
-        KeyCode::Char('c') | KeyCode::Char('C') => {
-            let _ = app.tx.send(NewsroomTransitions::Quit);
-        },
-        KeyCode::Down | KeyCode::Char('j') => {
-            let _ = app.tx.send(NewsroomTransitions::Down);
-        }
-        KeyCode::Up | KeyCode::Char('k') => {
-            let _ = app.tx.send(NewsroomTransitions::Up);
-        }
-        KeyCode::Char('l') => {
-            let _ = app.tx.send(NewsroomTransitions::FetchMedia(app.settings.sources.clone()));
-        }
-        KeyCode::Enter => {
+    if app.help.is_some() {
+        return handle_help_keys(key_event, app);
+    }
+    if app.command.is_some() {
+        return handle_command_keys(key_event, app);
+    }
+    if app.filter.editing {
+        return handle_search_keys(key_event, app);
+    }
+    if app.form.is_some() {
+        return handle_form_keys(key_event, app);
+    }
+    if app.reader.is_some() {
+        return handle_reader_keys(key_event, app);
+    }
+    if app.theme_picker.is_some() {
+        return handle_theme_keys(key_event, app);
+    }
+    let scopes: &[Scope] = match app.tab {
+        2 => &[Scope::Settings, Scope::Browse, Scope::Everywhere],
+        _ => &[Scope::Browse, Scope::Everywhere],
+    };
+    let Some(action) = app.settings.keymap.action(&key_event, scopes) else { return Ok(()) };
+    let transition = match action {
+        // Clear the search before quitting
+        Action::Back if app.filter.is_active() => NewsroomTransitions::CancelSearch,
+        Action::Quit | Action::Back => NewsroomTransitions::Quit,
+        Action::Down => NewsroomTransitions::Down,
+        Action::Up => NewsroomTransitions::Up,
+        Action::Left => NewsroomTransitions::Left,
+        Action::Right => NewsroomTransitions::Right,
+        Action::ImportOpml => NewsroomTransitions::OpenForm(FormAction::ImportOpml),
+        Action::ExportOpml => NewsroomTransitions::OpenForm(FormAction::ExportOpml),
+        Action::AddSource => NewsroomTransitions::OpenForm(FormAction::AddSource),
+        Action::EditSource => match app.settings_row {
+            Some(row) => NewsroomTransitions::OpenForm(FormAction::EditSource(row)),
+            None => return Ok(()),
+        },
+        Action::DeleteSource => match app.settings_row {
+            Some(row) => NewsroomTransitions::OpenForm(FormAction::DeleteSource(row)),
+            None => return Ok(()),
+        },
+        Action::ToggleSource => NewsroomTransitions::ToggleSource,
+        Action::PickTheme => NewsroomTransitions::OpenThemePicker,
+        Action::MoveSourceUp => NewsroomTransitions::MoveSourceUp,
+        Action::MoveSourceDown => NewsroomTransitions::MoveSourceDown,
+        Action::ToggleSidebar => NewsroomTransitions::ToggleSidebar,
+        Action::Command => NewsroomTransitions::OpenCommand,
+        Action::Refresh => NewsroomTransitions::FetchMedia(app.settings.enabled_sources()),
+        Action::ToggleRead => NewsroomTransitions::ToggleRead,
+        Action::ToggleSaved => NewsroomTransitions::ToggleSaved,
+        Action::CycleSort => NewsroomTransitions::CycleSort,
+        Action::Search => NewsroomTransitions::StartSearch,
+        Action::NextMatch => NewsroomTransitions::NextMatch,
+        Action::PreviousMatch => NewsroomTransitions::PreviousMatch,
+        // Choosing a source in the sidebar moves on to its articles
+        Action::Open if app.sidebar_focused && app.tab == 0 => NewsroomTransitions::Right,
+        Action::Open => NewsroomTransitions::OpenReader,
+        Action::OpenInBrowser => {
             app.open_selected();
+            return Ok(());
         },
-        KeyCode::Tab => {
+        Action::NextTab => {
             app.change_tab();
-        }
-        _ => {}
-    }
+            return Ok(());
+        },
+        Action::Help => NewsroomTransitions::ToggleHelp,
+        // Only listened for in the reader and the search line
+        Action::Close | Action::PageDown | Action::PageUp | Action::Top | Action::Bottom => return Ok(()),
+        Action::ConfirmSearch | Action::CancelSearch | Action::SearchNext | Action::SearchPrevious => return Ok(()),
+        // Only listened for in forms, the command line and the theme picker
+        Action::SubmitForm | Action::CancelForm | Action::NextField | Action::PreviousField => return Ok(()),
+        Action::SubmitCommand | Action::CancelCommand | Action::CompleteCommand => return Ok(()),
+        Action::ConfirmTheme | Action::CancelTheme => return Ok(()),
+    };
+    let _ = app.tx.send(transition);
+    Ok(())
+}
+
+/// Handles key events while the search line is open, where typing edits the query
+fn handle_search_keys(key_event: KeyEvent, app: &App) -> AppResult<()> {
+    let transition = match (app.settings.keymap.action(&key_event, &[Scope::Search]), key_event.code) {
+        (Some(Action::CancelSearch), _) => NewsroomTransitions::CancelSearch,
+        (Some(Action::ConfirmSearch), _) => NewsroomTransitions::ConfirmSearch,
+        (Some(Action::SearchNext), _) => NewsroomTransitions::NextMatch,
+        (Some(Action::SearchPrevious), _) => NewsroomTransitions::PreviousMatch,
+        _ if quits_text_input(&key_event, app) => NewsroomTransitions::Quit,
+        (_, KeyCode::Backspace) => NewsroomTransitions::SearchBackspace,
+        _ => match typed_char(&key_event) {
+            Some(c) => NewsroomTransitions::SearchInput(c),
+            None => return Ok(()),
+        },
+    };
+    let _ = app.tx.send(transition);
+    Ok(())
+}
+
+/// Handles key events while the command line is open, where typing edits the command.
+/// Backspacing past the start of the command closes the command line, like escape
+fn handle_command_keys(key_event: KeyEvent, app: &App) -> AppResult<()> {
+    let empty = app.command.as_ref().map_or("", |command| command.input.as_str()).is_empty();
+    let transition = match (app.settings.keymap.action(&key_event, &[Scope::Command]), key_event.code) {
+        (Some(Action::CancelCommand), _) => NewsroomTransitions::CancelCommand,
+        (Some(Action::SubmitCommand), _) => NewsroomTransitions::SubmitCommand,
+        (Some(Action::CompleteCommand), _) => NewsroomTransitions::CompleteCommand,
+        _ if quits_text_input(&key_event, app) => NewsroomTransitions::Quit,
+        (_, KeyCode::Backspace) if empty => NewsroomTransitions::CancelCommand,
+        (_, KeyCode::Backspace) => NewsroomTransitions::CommandBackspace,
+        _ => match typed_char(&key_event) {
+            Some(c) => NewsroomTransitions::CommandInput(c),
+            None => return Ok(()),
+        },
+    };
+    let _ = app.tx.send(transition);
+    Ok(())
+}
+
+/// Handles key events while the list of keys is showing, which scrolls like the reader
+fn handle_help_keys(key_event: KeyEvent, app: &App) -> AppResult<()> {
+    let page = Help::text_height(app.viewport).max(1) as i32;
+    let transition = match (key_event.code, app.settings.keymap.action(&key_event, &[Scope::Reader, Scope::Everywhere])) {
+        (KeyCode::Esc, _) | (_, Some(Action::Close | Action::Help)) => NewsroomTransitions::ToggleHelp,
+        (_, Some(Action::Quit)) => NewsroomTransitions::Quit,
+        (_, Some(Action::Down)) => NewsroomTransitions::ScrollHelp(1),
+        (_, Some(Action::Up)) => NewsroomTransitions::ScrollHelp(-1),
+        (_, Some(Action::PageDown)) => NewsroomTransitions::ScrollHelp(page),
+        (_, Some(Action::PageUp)) => NewsroomTransitions::ScrollHelp(-page),
+        (_, Some(Action::Top)) => NewsroomTransitions::ScrollHelp(i32::MIN),
+        (_, Some(Action::Bottom)) => NewsroomTransitions::ScrollHelp(i32::MAX),
+        _ => return Ok(()),
+    };
+    let _ = app.tx.send(transition);
+    Ok(())
+}
+
+/// Handles key events while picking a theme, where moving previews each theme
+fn handle_theme_keys(key_event: KeyEvent, app: &App) -> AppResult<()> {
+    let Some(action) = app.settings.keymap.action(&key_event, &[Scope::ThemePicker, Scope::Everywhere]) else { return Ok(()) };
+    let transition = match action {
+        Action::CancelTheme => NewsroomTransitions::CancelThemePicker,
+        Action::ConfirmTheme => NewsroomTransitions::ConfirmTheme,
+        Action::Down => NewsroomTransitions::Down,
+        Action::Up => NewsroomTransitions::Up,
+        _ => return Ok(()),
+    };
+    let _ = app.tx.send(transition);
+    Ok(())
+}
+
+/// Handles key events while an article is open in the reader, where the movement keys scroll the article
+fn handle_reader_keys(key_event: KeyEvent, app: &App) -> AppResult<()> {
+    let page = Reader::text_size(app.viewport).1.max(1) as i32;
+    let Some(action) = app.settings.keymap.action(&key_event, &[Scope::Reader, Scope::Everywhere]) else { return Ok(()) };
+    let transition = match action {
+        Action::Close => NewsroomTransitions::CloseReader,
+        Action::Quit => NewsroomTransitions::Quit,
+        Action::Down => NewsroomTransitions::ScrollReader(1),
+        Action::Up => NewsroomTransitions::ScrollReader(-1),
+        Action::PageDown => NewsroomTransitions::ScrollReader(page),
+        Action::PageUp => NewsroomTransitions::ScrollReader(-page),
+        Action::Top => NewsroomTransitions::ScrollReader(i32::MIN),
+        Action::Bottom => NewsroomTransitions::ScrollReader(i32::MAX),
+        Action::OpenInBrowser => {
+            app.open_selected();
+            return Ok(());
+        },
+        Action::Help => NewsroomTransitions::ToggleHelp,
+        _ => return Ok(()),
+    };
+    let _ = app.tx.send(transition);
     Ok(())
 }
+
+/// Handles key events while a form is open, where typing edits the focused field
+fn handle_form_keys(key_event: KeyEvent, app: &App) -> AppResult<()> {
+    let transition = match (app.settings.keymap.action(&key_event, &[Scope::Form]), key_event.code) {
+        (Some(Action::CancelForm), _) => NewsroomTransitions::CancelForm,
+        (Some(Action::SubmitForm), _) => NewsroomTransitions::SubmitForm,
+        (Some(Action::NextField), _) => NewsroomTransitions::NextField,
+        (Some(Action::PreviousField), _) => NewsroomTransitions::PreviousField,
+        _ if quits_text_input(&key_event, app) => NewsroomTransitions::Quit,
+        (_, KeyCode::Backspace) => NewsroomTransitions::FormBackspace,
+        _ => match typed_char(&key_event) {
+            Some(c) => NewsroomTransitions::FormInput(c),
+            None => return Ok(()),
+        },
+    };
+    let _ = app.tx.send(transition);
+    Ok(())
+}
+
+/// Whether a key quits while text is being typed. Only chords held with Ctrl or Alt can, since
+/// anything else is typed, so Ctrl-C quits from a search while q is added to it
+fn quits_text_input(key_event: &KeyEvent, app: &App) -> bool {
+    key_event.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        && app.settings.keymap.action(key_event, &[Scope::Everywhere]) == Some(Action::Quit)
+}
+
+/// The character a key types into a text input, keys held with Ctrl or Alt don't type anything
+fn typed_char(key_event: &KeyEvent) -> Option<char> {
+    match key_event.code {
+        KeyCode::Char(c) if !key_event.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => Some(c),
+        _ => None,
+    }
+}
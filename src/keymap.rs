@@ -0,0 +1,515 @@
+use std::{collections::BTreeMap, fmt};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+/// Something a key can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Up,
+    Down,
+    OpenInBrowser,
+    Help,
+    /// Clears the search, or quits when there is no search
+    Back,
+    NextTab,
+    Left,
+    Right,
+    /// Reads the highlighted article, or moves on to the articles of the highlighted source in the sidebar
+    Open,
+    Refresh,
+    ToggleRead,
+    ToggleSaved,
+    CycleSort,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    ToggleSidebar,
+    /// Opens the command line
+    Command,
+    AddSource,
+    EditSource,
+    DeleteSource,
+    ToggleSource,
+    MoveSourceUp,
+    MoveSourceDown,
+    ImportOpml,
+    ExportOpml,
+    PickTheme,
+    /// Keeps the search typed so far and goes back to the list
+    ConfirmSearch,
+    CancelSearch,
+    SearchNext,
+    SearchPrevious,
+    SubmitForm,
+    CancelForm,
+    NextField,
+    PreviousField,
+    /// Runs the command typed
+    SubmitCommand,
+    CancelCommand,
+    /// Completes the command's name, or its argument once the name is typed
+    CompleteCommand,
+    /// Keeps the theme being previewed
+    ConfirmTheme,
+    /// Goes back to the theme in use before the picker was opened
+    CancelTheme,
+    /// Closes the reader
+    Close,
+    PageDown,
+    PageUp,
+    Top,
+    Bottom,
+}
+
+/// Where an action's keys are listened for. Keys are looked up in the most specific scope first,
+/// so the settings tab's keys shadow the ones for browsing articles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// The tabs and the reader
+    Everywhere,
+    /// All three tabs
+    Browse,
+    /// The settings tab
+    Settings,
+    /// The search line, while it is being typed into. Keys not bound here are typed into the search
+    Search,
+    /// The reader
+    Reader,
+    /// The form for adding, editing, deleting, importing or exporting sources. Keys not bound here are typed into the focused field
+    Form,
+    /// The command line. Keys not bound here are typed into the command
+    Command,
+    /// The theme picker
+    ThemePicker,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 8] = [
+        Scope::Everywhere, Scope::Browse, Scope::Settings, Scope::Search, Scope::Reader, Scope::Form, Scope::Command, Scope::ThemePicker,
+    ];
+
+    /// Whether one key bound in both scopes would be ambiguous. Nothing outside the search line, a form or the command line
+    /// is listened for while typing into them
+    fn overlaps(self, other: Scope) -> bool {
+        let typed = |scope| matches!(scope, Scope::Search | Scope::Form | Scope::Command);
+        match (self, other) {
+            _ if typed(self) || typed(other) => self == other,
+            (Scope::Everywhere, _) | (_, Scope::Everywhere) => true,
+            _ => self == other,
+        }
+    }
+
+    /// The heading of the scope in the help
+    pub fn title(self) -> &'static str {
+        match self {
+            Scope::Everywhere => "Everywhere",
+            Scope::Browse => "News list",
+            Scope::Settings => "Settings",
+            Scope::Search => "Search",
+            Scope::Reader => "Reader",
+            Scope::Form => "Forms",
+            Scope::Command => "Command line",
+            Scope::ThemePicker => "Theme picker",
+        }
+    }
+}
+
+impl Action {
+    pub const ALL: [Action; 46] = [
+        Action::Quit, Action::Up, Action::Down, Action::OpenInBrowser, Action::Help,
+        Action::Back, Action::NextTab, Action::Left, Action::Right, Action::Open, Action::Refresh, Action::ToggleRead,
+        Action::ToggleSaved, Action::CycleSort, Action::Search, Action::NextMatch, Action::PreviousMatch, Action::ToggleSidebar, Action::Command,
+        Action::AddSource, Action::EditSource, Action::DeleteSource, Action::ToggleSource, Action::MoveSourceUp,
+        Action::MoveSourceDown, Action::ImportOpml, Action::ExportOpml, Action::PickTheme,
+        Action::ConfirmSearch, Action::CancelSearch, Action::SearchNext, Action::SearchPrevious,
+        Action::SubmitForm, Action::CancelForm, Action::NextField, Action::PreviousField,
+        Action::SubmitCommand, Action::CancelCommand, Action::CompleteCommand, Action::ConfirmTheme, Action::CancelTheme,
+        Action::Close, Action::PageDown, Action::PageUp, Action::Top, Action::Bottom,
+    ];
+
+    /// The action's name in the [keys] table, where it listens for keys, the keys it is bound to by default
+    /// and what it does
+    fn info(self) -> (&'static str, Scope, &'static [&'static str], &'static str) {
+        match self {
+            Action::Quit => ("quit", Scope::Everywhere, &["q", "ctrl-c"], "Quit newsroom"),
+            Action::Up => ("up", Scope::Everywhere, &["up", "k"], "Move up"),
+            Action::Down => ("down", Scope::Everywhere, &["down", "j"], "Move down"),
+            Action::OpenInBrowser => ("open_in_browser", Scope::Everywhere, &["o"], "Open the article in the browser"),
+            Action::Help => ("help", Scope::Everywhere, &["?"], "Show every key"),
+            Action::Back => ("back", Scope::Browse, &["esc"], "Clear the search, or quit"),
+            Action::NextTab => ("next_tab", Scope::Browse, &["tab"], "Go to the next tab"),
+            Action::Left => ("left", Scope::Browse, &["left"], "Focus the source sidebar"),
+            Action::Right => ("right", Scope::Browse, &["right"], "Focus the article list"),
+            Action::Open => ("open", Scope::Browse, &["enter"], "Read the article, or show the source's articles"),
+            Action::Refresh => ("refresh", Scope::Browse, &["l"], "Fetch the latest articles"),
+            Action::ToggleRead => ("toggle_read", Scope::Browse, &["m"], "Mark read or unread"),
+            Action::ToggleSaved => ("toggle_saved", Scope::Browse, &["s"], "Save the article for later, or unsave it"),
+            Action::CycleSort => ("cycle_sort", Scope::Browse, &["S"], "Change how articles are sorted"),
+            Action::Search => ("search", Scope::Browse, &["/"], "Search the headlines"),
+            Action::NextMatch => ("next_match", Scope::Browse, &["n"], "Go to the next match"),
+            Action::PreviousMatch => ("previous_match", Scope::Browse, &["N"], "Go to the previous match"),
+            Action::ToggleSidebar => ("toggle_sidebar", Scope::Browse, &["b"], "Show or hide the source sidebar"),
+            Action::Command => ("command", Scope::Browse, &[":"], "Type a command, like :sort newest"),
+            Action::AddSource => ("add_source", Scope::Settings, &["a"], "Add a source"),
+            Action::EditSource => ("edit_source", Scope::Settings, &["e", "enter"], "Edit the source"),
+            Action::DeleteSource => ("delete_source", Scope::Settings, &["d", "delete"], "Delete the source"),
+            Action::ToggleSource => ("toggle_source", Scope::Settings, &["space"], "Turn the source on or off"),
+            Action::MoveSourceUp => ("move_source_up", Scope::Settings, &["K"], "Move the source up"),
+            Action::MoveSourceDown => ("move_source_down", Scope::Settings, &["J"], "Move the source down"),
+            Action::ImportOpml => ("import_opml", Scope::Settings, &["i"], "Import sources from OPML"),
+            Action::ExportOpml => ("export_opml", Scope::Settings, &["x"], "Export sources to OPML"),
+            Action::PickTheme => ("pick_theme", Scope::Settings, &["t"], "Pick a theme"),
+            Action::ConfirmSearch => ("confirm_search", Scope::Search, &["enter"], "Keep the search and go back to the list"),
+            Action::CancelSearch => ("cancel_search", Scope::Search, &["esc"], "Clear the search"),
+            Action::SearchNext => ("search_next", Scope::Search, &["down"], "Go to the next match"),
+            Action::SearchPrevious => ("search_previous", Scope::Search, &["up"], "Go to the previous match"),
+            Action::SubmitForm => ("submit_form", Scope::Form, &["enter"], "Confirm the form"),
+            Action::CancelForm => ("cancel_form", Scope::Form, &["esc"], "Close the form without changing anything"),
+            Action::NextField => ("next_field", Scope::Form, &["tab", "down"], "Go to the next field"),
+            Action::PreviousField => ("previous_field", Scope::Form, &["backtab", "up"], "Go to the previous field"),
+            Action::SubmitCommand => ("submit_command", Scope::Command, &["enter"], "Run the command"),
+            Action::CancelCommand => ("cancel_command", Scope::Command, &["esc"], "Close the command line"),
+            Action::CompleteCommand => ("complete_command", Scope::Command, &["tab"], "Complete the command"),
+            Action::ConfirmTheme => ("confirm_theme", Scope::ThemePicker, &["enter"], "Keep the theme"),
+            Action::CancelTheme => ("cancel_theme", Scope::ThemePicker, &["esc"], "Go back to the theme in use"),
+            Action::Close => ("close", Scope::Reader, &["esc", "backspace", "left"], "Go back to the list"),
+            Action::PageDown => ("page_down", Scope::Reader, &["pagedown", "space"], "Scroll down a page"),
+            Action::PageUp => ("page_up", Scope::Reader, &["pageup"], "Scroll up a page"),
+            Action::Top => ("top", Scope::Reader, &["home", "g"], "Go to the top"),
+            Action::Bottom => ("bottom", Scope::Reader, &["end", "G"], "Go to the bottom"),
+        }
+    }
+
+    /// The action's name in the [keys] table of newsroom.toml
+    pub fn name(self) -> &'static str {
+        self.info().0
+    }
+
+    pub fn scope(self) -> Scope {
+        self.info().1
+    }
+
+    /// What the action does, for the help
+    pub fn description(self) -> &'static str {
+        self.info().3
+    }
+
+    /// Looks an action up by its name in the [keys] table
+    ///
+    /// Arguments
+    /// * name - The name of the action
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+/// A key along with the modifiers held with it, like Ctrl-c
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    /// Creates a chord, folding shift into the key for characters, since 'S' already says shift was held
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyChord {
+        let modifiers = modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => KeyChord {
+                code: KeyCode::Char(c.to_ascii_uppercase()),
+                modifiers: modifiers - KeyModifiers::SHIFT,
+            },
+            KeyCode::BackTab => KeyChord { code, modifiers: modifiers - KeyModifiers::SHIFT },
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => KeyChord { code: KeyCode::BackTab, modifiers: modifiers - KeyModifiers::SHIFT },
+            _ => KeyChord { code, modifiers },
+        }
+    }
+
+    /// Reads a chord written like "q", "S", "ctrl-c", "alt+enter" or "pagedown"
+    ///
+    /// Arguments
+    /// * text - The chord as written in newsroom.toml
+    pub fn parse(text: &str) -> Result<KeyChord, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text.trim();
+        // A lone "-" or "+" is the key itself, not a separator
+        while let Some((modifier, key)) = rest.split_once(['-', '+']).filter(|(modifier, _)| !modifier.is_empty()) {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => break,
+            };
+            rest = key;
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "space" => KeyCode::Char(' '),
+                key => match key.strip_prefix('f').and_then(|number| number.parse::<u8>().ok()) {
+                    Some(number @ 1..=12) => KeyCode::F(number),
+                    _ => return Err(format!("unknown key \"{}\"", text)),
+                },
+            },
+        };
+        Ok(KeyChord::new(code, modifiers))
+    }
+}
+
+impl From<&KeyEvent> for KeyChord {
+    fn from(event: &KeyEvent) -> Self {
+        KeyChord::new(event.code, event.modifiers)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Enter => write!(f, "↵"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::BackTab => write!(f, "Shift-Tab"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Delete => write!(f, "Del"),
+            KeyCode::Insert => write!(f, "Ins"),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::F(number) => write!(f, "F{}", number),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// The keys for an action in newsroom.toml, either one key or a list of them
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Which keys trigger which actions
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    /// The keys of every action, in the order of Action::ALL
+    bindings: Vec<(Action, Vec<KeyChord>)>,
+}
+
+impl Keymap {
+    /// The keymap with every action on its default keys
+    pub fn new() -> Keymap {
+        let bindings = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let keys = action.info().2.iter().map(|key| KeyChord::parse(key).expect("default keys are valid")).collect();
+                (action, keys)
+            })
+            .collect();
+        Keymap { bindings }
+    }
+
+    /// Builds the keymap from the [keys] table of newsroom.toml, where each action listed replaces its default keys.
+    /// Returns the keymap along with any problems found, where a key bound to two actions is kept by the one
+    /// listed in the table, or by the first action if neither or both are listed
+    ///
+    /// Arguments
+    /// * keys - The [keys] table, action names to keys
+    pub fn load(keys: &BTreeMap<String, KeyList>) -> (Keymap, Vec<String>) {
+        let mut keymap = Keymap::new();
+        let mut problems = vec![];
+        let mut customised = vec![];
+        for (name, list) in keys {
+            let Some(action) = Action::from_name(name) else {
+                problems.push(format!("[keys] has no action called \"{}\"", name));
+                continue;
+            };
+            let texts = match list {
+                KeyList::One(text) => vec![text.clone()],
+                KeyList::Many(texts) => texts.clone(),
+            };
+            let unbound = texts.is_empty();
+            let mut chords = vec![];
+            for text in texts {
+                match KeyChord::parse(&text) {
+                    Ok(chord) => chords.push(chord),
+                    Err(e) => problems.push(format!("[keys] {}: {}", name, e)),
+                }
+            }
+            // Only an empty list unbinds an action, so a typo can't leave something like quit out of reach
+            if chords.is_empty() && !unbound {
+                problems.push(format!("[keys] {}: none of its keys could be read, keeping the default", name));
+                continue;
+            }
+            keymap.set(action, chords);
+            customised.push(action);
+        }
+
+        let order = customised
+            .iter()
+            .copied()
+            .chain(Action::ALL.into_iter().filter(|action| !customised.contains(action)))
+            .collect::<Vec<_>>();
+        let mut taken: Vec<(KeyChord, Action)> = vec![];
+        for action in order {
+            let chords = keymap.keys(action).to_vec();
+            let mut kept = vec![];
+            for chord in chords {
+                match taken.iter().find(|(other, owner)| *other == chord && owner.scope().overlaps(action.scope())) {
+                    Some((_, owner)) => problems.push(format!(
+                        "[keys] {} is bound to both {} and {}, keeping it for {}",
+                        chord, owner.name(), action.name(), owner.name()
+                    )),
+                    None => {
+                        taken.push((chord, action));
+                        kept.push(chord);
+                    },
+                }
+            }
+            keymap.set(action, kept);
+        }
+        (keymap, problems)
+    }
+
+    fn set(&mut self, action: Action, chords: Vec<KeyChord>) {
+        if let Some((_, keys)) = self.bindings.iter_mut().find(|(bound, _)| *bound == action) {
+            *keys = chords;
+        }
+    }
+
+    /// The keys bound to an action
+    pub fn keys(&self, action: Action) -> &[KeyChord] {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map_or(&[], |(_, keys)| keys.as_slice())
+    }
+
+    /// The keys bound to an action written out for the bottom bar, like "q/Ctrl-c", None if it has no keys
+    pub fn label(&self, action: Action) -> Option<String> {
+        let keys = self.keys(action);
+        match keys.is_empty() {
+            true => None,
+            false => Some(keys.iter().map(|key| key.to_string()).collect::<Vec<_>>().join("/")),
+        }
+    }
+
+    /// Finds the action a key press triggers, looking through the scopes in order
+    ///
+    /// Arguments
+    /// * event - The key press
+    /// * scopes - The scopes listening for keys, most specific first
+    pub fn action(&self, event: &KeyEvent, scopes: &[Scope]) -> Option<Action> {
+        let chord = KeyChord::from(event);
+        scopes.iter().find_map(|scope| {
+            self.bindings
+                .iter()
+                .find(|(action, keys)| action.scope() == *scope && keys.contains(&chord))
+                .map(|(action, _)| *action)
+        })
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        Keymap::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_chords(){
+        assert_eq!(KeyChord::parse("ctrl-c"), Ok(KeyChord { code: KeyCode::Char('c'), modifiers: KeyModifiers::CONTROL }));
+        assert_eq!(KeyChord::parse("Shift+s"), KeyChord::parse("S"));
+        assert_eq!(KeyChord::parse("shift-tab"), KeyChord::parse("backtab"));
+        assert_eq!(KeyChord::parse("-").map(|chord| chord.code), Ok(KeyCode::Char('-')));
+        assert_eq!(KeyChord::parse("alt-+").map(|chord| chord.to_string()), Ok("Alt-+".to_string()));
+        assert_eq!(KeyChord::parse("PgDn").map(|chord| chord.to_string()), Ok("PgDn".to_string()));
+        assert!(KeyChord::parse("f13").is_err());
+        assert!(KeyChord::parse("hyper-x").is_err());
+
+        // Terminals send shifted letters with the shift modifier as well
+        let event = KeyEvent::new(KeyCode::Char('S'), KeyModifiers::SHIFT);
+        assert_eq!(KeyChord::from(&event), KeyChord::parse("S").unwrap());
+    }
+
+    #[test]
+    fn test_keymap(){
+        let (keymap, problems) = Keymap::load(&BTreeMap::new());
+        assert!(problems.is_empty(), "{:?}", problems);
+        let browse = [Scope::Browse, Scope::Everywhere];
+        let settings = [Scope::Settings, Scope::Browse, Scope::Everywhere];
+        assert_eq!(keymap.action(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL), &browse), Some(Action::Quit));
+        assert_eq!(keymap.action(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE), &browse), None);
+        assert_eq!(keymap.action(&KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), &browse), Some(Action::Open));
+        assert_eq!(keymap.action(&KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), &settings), Some(Action::EditSource));
+        assert_eq!(keymap.label(Action::Quit).as_deref(), Some("q/Ctrl-c"));
+
+        let keys: BTreeMap<String, KeyList> = toml::from_str(r#"
+            refresh = ["r", "m"]
+            quit = "ctrl-q"
+            open_in_browser = []
+            teleport = "t"
+            search = ["/", "ctrl-banana"]
+        "#).unwrap();
+        let (keymap, problems) = Keymap::load(&keys);
+        assert_eq!(problems, vec![
+            "[keys] search: unknown key \"ctrl-banana\"",
+            "[keys] has no action called \"teleport\"",
+            "[keys] m is bound to both refresh and toggle_read, keeping it for refresh",
+        ]);
+        assert_eq!(keymap.action(&KeyEvent::new(KeyCode::Char('m'), KeyModifiers::NONE), &browse), Some(Action::Refresh));
+        assert_eq!(keymap.label(Action::ToggleRead), None);
+        assert_eq!(keymap.label(Action::OpenInBrowser), None);
+        assert_eq!(keymap.action(&KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE), &browse), None);
+
+        // Forms only listen for their own keys, so they can reuse the tabs' keys but not each other's
+        let keys: BTreeMap<String, KeyList> = toml::from_str(r#"cancel_form = ["tab", "q"]"#).unwrap();
+        let (keymap, problems) = Keymap::load(&keys);
+        assert_eq!(problems, vec!["[keys] Tab is bound to both cancel_form and next_field, keeping it for cancel_form"]);
+        assert_eq!(keymap.action(&KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE), &[Scope::Form]), Some(Action::CancelForm));
+        assert_eq!(keymap.action(&KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE), &browse), Some(Action::Quit));
+
+        // An action whose keys all fail to parse keeps its defaults rather than becoming unreachable
+        let keys: BTreeMap<String, KeyList> = toml::from_str(r#"quit = ["ctrl-banana", "hyper-q"]"#).unwrap();
+        let (keymap, problems) = Keymap::load(&keys);
+        assert_eq!(problems.last().map(String::as_str), Some("[keys] quit: none of its keys could be read, keeping the default"));
+        assert_eq!(keymap.label(Action::Quit).as_deref(), Some("q/Ctrl-c"));
+    }
+}
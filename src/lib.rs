@@ -13,12 +13,24 @@ pub mod handler;
 /// Settings.
 pub mod settings;
 
+/// Colour themes, built in and from newsroom.toml
+pub mod theme;
+
+/// Key bindings, with defaults that newsroom.toml can override
+pub mod keymap;
+
+/// The ':' command line, which carries commands out through the app's transitions
+pub mod command;
+
 /// Root ui
 pub mod root;
 
 /// Tabs
 pub mod tabs;
 
+/// Rendering article HTML as terminal text
+pub mod richtext;
+
 /// Newsroomcore stores all our background app logic in a single module.
 /// It provides structures and services for defining news articles and news sources
 /// As well as fetching them from RSS channels.
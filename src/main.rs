@@ -1,25 +1,110 @@
 use log::LevelFilter;
-use newsroom::app::{App, AppResult};
+use newsroom::app::{App, AppResult, Stores};
 use newsroom::event::{Event, EventHandler};
 use newsroom::handler::handle_key_events;
-use newsroom::newsroomcore::newsroomstate::NewsroomTransitions;
+use newsroom::newsroomcore::opml::write_opml;
+use newsroom::settings::{Settings, data_dir, expand_home};
 use newsroom::tui::Tui;
 
-use std::io;
+use argh::FromArgs;
+use std::io::{self, IsTerminal, Write};
 
 use tui::backend::CrosstermBackend;
 use tui::Terminal;
 
+/// Instantly get a personalized summary of headline articles from your favourite RSS sources
+#[derive(FromArgs)]
+struct Args {
+    /// show the articles stored by the last session without fetching anything
+    #[argh(switch)]
+    offline: bool,
+    #[argh(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum Command {
+    Import(ImportCommand),
+    Export(ExportCommand),
+}
+
+/// Add the sources listed in an OPML file to newsroom.toml
+#[derive(FromArgs)]
+#[argh(subcommand, name = "import")]
+struct ImportCommand {
+    /// the OPML file to import
+    #[argh(positional)]
+    file: String,
+}
+
+/// Write the sources in newsroom.toml as OPML
+#[derive(FromArgs)]
+#[argh(subcommand, name = "export")]
+struct ExportCommand {
+    /// the OPML file to write, the OPML is printed if this is left out
+    #[argh(positional)]
+    file: Option<String>,
+}
+
+/// Runs a subcommand instead of the interface
+fn run_command(command: Command) -> AppResult<()> {
+    let mut settings = Settings::new();
+    match command {
+        Command::Import(import) => {
+            let file = expand_home(&import.file);
+            let result = settings.import_opml(&file)?;
+            println!("Imported {} new sources from {}", result.added, file.display());
+            for skipped in result.skipped {
+                eprintln!("Skipped {}", skipped);
+            }
+        },
+        Command::Export(ExportCommand { file: Some(file) }) => {
+            let file = expand_home(&file);
+            settings.export_opml(&file)?;
+            println!("Exported {} sources to {}", settings.sources.len(), file.display());
+        },
+        Command::Export(ExportCommand { file: None }) => {
+            io::stdout().write_all(write_opml(&settings.sources).as_bytes())?;
+        },
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> AppResult<()> {
+    let args: Args = argh::from_env();
+    if let Some(command) = args.command {
+        return run_command(command);
+    }
+
     // Setup logging, only used for development
     if cfg!(debug_assertions) {
         let _ = simple_logging::log_to_file("newsroom.log", LevelFilter::Info);
     }
 
     // Create an application.
-    let mut app = App::new();
-    let _ = app.tx.send(NewsroomTransitions::FetchMedia(app.settings.sources.clone()));
+    let stores = match data_dir() {
+        Ok(dir) => Stores::open(&dir),
+        Err(_) => Stores::disabled(),
+    };
+    let mut app = App::new(Settings::new(), stores);
+    app.offline = args.offline;
+    // Report mistakes in newsroom.toml before the interface takes over the screen
+    if !app.settings.problems.is_empty() {
+        eprintln!("Problems in newsroom.toml:");
+        for problem in &app.settings.problems {
+            eprintln!("  {}", problem);
+        }
+        // Only wait when someone is there to press enter, a piped stdin would block or skip past it
+        if io::stdin().is_terminal() {
+            eprint!("Press enter to continue");
+            io::stdin().read_line(&mut String::new())?;
+        }
+    }
+    app.start();
+    let (width, height) = crossterm::terminal::size()?;
+    app.resize(width, height);
 
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(io::stderr());
@@ -41,10 +126,11 @@ async fn main() -> AppResult<()> {
             },
             Event::Key(key_event) => handle_key_events(key_event, &app)?,
             Event::Mouse(_) => {},
-            Event::Resize(_, _) => {},
+            Event::Resize(width, height) => app.resize(width, height),
         }
 
-        app.poll_and_run_action().await;
+        // Run every transition that queued up while we were waiting on events
+        while app.poll_and_run_action().await {}
     }
 
     // Exit the user interface.
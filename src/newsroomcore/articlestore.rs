@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+use log::{info, warn};
+use super::{newsarticle::NewsArticle, datasources::DataSources, storage::{read_json, write_json}};
+
+/// Keeps the last fetched articles on disk, so newsroom has something to show before
+/// (or without) reaching the network
+#[derive(Debug, Clone)]
+pub struct ArticleStore {
+    path: Option<PathBuf>,
+}
+
+impl ArticleStore {
+    /// Creates a store which keeps articles in the given file
+    pub fn new(path: PathBuf) -> ArticleStore {
+        ArticleStore { path: Some(path) }
+    }
+
+    /// Creates a store which never keeps anything
+    pub fn disabled() -> ArticleStore {
+        ArticleStore { path: None }
+    }
+
+    /// Loads the stored articles, dropping any from sources which are no longer configured
+    ///
+    /// Arguments
+    /// * sources - The currently configured sources
+    pub fn load(&self, sources: &[DataSources]) -> Vec<NewsArticle> {
+        let Some(path) = &self.path else { return vec![] };
+        let articles: Vec<NewsArticle> = read_json(path).unwrap_or_default();
+        let articles: Vec<NewsArticle> = articles
+            .into_iter()
+            .filter(|article| sources.iter().any(|source| source.url == article.source.url))
+            .map(|mut article| {
+                // Stored before articles had ids
+                if article.id.is_empty() {
+                    article.id = NewsArticle::id_from_link(&article.link);
+                }
+                article
+            })
+            .collect();
+        info!("Loaded {} stored articles", articles.len());
+        articles
+    }
+
+    /// Replaces the stored articles
+    ///
+    /// Arguments
+    /// * articles - The articles to keep
+    pub fn save(&self, articles: &[NewsArticle]) {
+        let Some(path) = &self.path else { return };
+        if let Err(e) = write_json(path, &articles) {
+            warn!("Couldn't store articles in {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::newsroomcore::storage::TempPath;
+
+    #[test]
+    fn test_store_roundtrip(){
+        let path = TempPath::new("articles.json");
+        let store = ArticleStore::new(path.to_path_buf());
+        let kept = DataSources::new("kept", "https://kept.example.com/feed");
+        let removed = DataSources::new("removed", "https://removed.example.com/feed");
+        let article = |source: &DataSources, link: &str| NewsArticle {
+            authors: vec!["Jane Reporter".to_string()],
+            summary: "summary".to_string(),
+            published: chrono::DateTime::parse_from_rfc3339("2023-10-03T18:30:00+02:00").ok(),
+            ..NewsArticle::test(source, link)
+        };
+
+        let sources = vec![kept.clone()];
+
+        // Nothing stored yet
+        assert!(store.load(&sources).is_empty());
+
+        store.save(&[article(&kept, "a"), article(&removed, "b")]);
+        let loaded = store.load(&sources);
+
+        // Articles from sources no longer configured are dropped, the rest come back intact
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].link, "a");
+        assert_eq!(loaded[0].source, kept);
+        assert_eq!(loaded[0].published, article(&kept, "a").published);
+    }
+
+    // Test that articles stored before they had ids still load, with the id their link would give them
+    #[test]
+    fn test_load_without_ids(){
+        let path = TempPath::new("old-articles.json");
+        let source = DataSources::new("kept", "https://kept.example.com/feed");
+        let stored = r#"[{"authors": [], "title": "Old", "summary": "", "link": "https://kept.example.com/old",
+            "source": {"name": "kept", "url": "https://kept.example.com/feed"}, "published": null}]"#;
+        std::fs::write(&path, stored).unwrap();
+        let loaded = ArticleStore::new(path.to_path_buf()).load(&[source]);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].id, NewsArticle::id_from_link("https://kept.example.com/old"));
+    }
+}
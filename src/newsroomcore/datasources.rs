@@ -1,57 +1,72 @@
 use tokio::sync::mpsc::Sender;
 use serde::{Deserialize, Serialize};
-use super::{newsfetchrss::get_channel, newsarticle::NewsArticle};
+use super::{newsfetchrss::get_channel_with_retries, newsarticle::NewsArticle, fetcherror::FetchError, fetchpolicy::FetchPolicy, httpcache::HttpCache};
 
 // Represents our data providers
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct DataSources {
     pub name: String,
     pub url: String,
+    /// The folder the source is filed under, nested folders are separated by '/'
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Overrides the request timeout from the [fetch] settings for this source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Overrides the retry count from the [fetch] settings for this source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    /// Fetch each article's page and show the whole story in the reader, for feeds which only carry a teaser
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub full_text: bool,
+    /// Keeps the source in the config without fetching it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
 }
 
 impl DataSources {
-    /// Take in a channel and reformat into a vector of news articles, streaming them async over a channel
+    /// Creates a source using the global fetch settings
+    pub fn new(name: &str, url: &str) -> DataSources {
+        DataSources {
+            name: name.to_string(),
+            url: url.to_string(),
+            category: None,
+            timeout_secs: None,
+            retries: None,
+            full_text: false,
+            disabled: false,
+        }
+    }
+
+    /// Checks the source has a name and a web url, returning what is wrong with it otherwise
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("The name can't be empty".to_string());
+        }
+        match url::Url::parse(&self.url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") && url.host().is_some() => Ok(()),
+            Ok(_) => Err("The URL must start with http:// or https://".to_string()),
+            Err(e) => Err(format!("The URL isn't valid: {}", e)),
+        }
+    }
+
+    /// Take in a channel and reformat into a vector of news articles, streaming them async over a channel.
+    /// Returns the number of articles sent
     ///
     /// Arguments
+    /// * client - The http client to fetch with
+    /// * policy - The global fetch settings, this source's overrides are applied on top
+    /// * cache - Where the last response from each feed is remembered
     /// * tx - A channel which we can send fetched articles over
-    pub(crate) async fn stream_articles(self, tx: Sender<NewsArticle>) -> Result<(), ()>{
-        if let Ok(channel) = get_channel(&self.url).await {
-            let _articles: Vec<NewsArticle> = Vec::new();
-            for item in channel.items(){
-                match item.description(){
-                    Some(description) => {
-                        // Extract the data we need from item
-        
-                        // Author
-                        let author: Vec<String> = match item.author(){
-                            Some(auth) => vec![auth.to_string()],
-                            None => vec!["".to_string()],
-                        };
-        
-                        // Title
-                        let title: String = match item.title() {
-                            Some(tit) => tit.to_string(),
-                            None => "".to_string(),
-                        };
-                        
-                        // Summary (We've already unwrapped this)
-                        let summary = description.to_string();
-        
-                        // Link
-                        let link = match item.link() {
-                            Some(url) => url.to_string(),
-                            None => "".to_string(),
-                        };
-        
-                        let article_to_push: NewsArticle = NewsArticle{ authors: author, title, summary, link, source: self.clone() };
-                        let _ = tx.send(article_to_push).await;
-                    },
-                    None => {},
-                }
-            }
-        } else {return Err(())}
-    
-        Ok(())
+    pub(crate) async fn stream_articles(self, client: reqwest::Client, policy: FetchPolicy, cache: HttpCache, tx: Sender<NewsArticle>) -> Result<usize, FetchError>{
+        let feed = get_channel_with_retries(&client, &self.url, &policy.for_source(&self), &cache).await?;
+        let articles = feed.articles(&self);
+        let count = articles.len();
+        for article in articles {
+            let _ = tx.send(article).await;
+        }
+
+        Ok(count)
     }
 }
 
@@ -62,12 +77,21 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn test_validate(){
+        assert_eq!(DataSources::new("feed", "https://example.com/feed.xml").validate(), Ok(()));
+        assert!(DataSources::new(" ", "https://example.com/feed.xml").validate().is_err());
+        assert!(DataSources::new("feed", "example.com/feed.xml").validate().is_err());
+        assert!(DataSources::new("feed", "ftp://example.com/feed.xml").validate().is_err());
+        assert!(DataSources::new("feed", "file:///feed.xml").validate().is_err());
+    }
+
     #[tokio::test]
     async fn streaming_test(){
-        let cbc = DataSources{name: "cbc".to_string(), url: "https://www.cbc.ca/cmlink/rss-topstories".to_string()};
+        let cbc = DataSources::new("cbc", "https://www.cbc.ca/cmlink/rss-topstories");
         let (tx, mut rx): (Sender<NewsArticle>, Receiver<NewsArticle>) = mpsc::channel(100);
 
-        tokio::spawn(cbc.stream_articles(tx));
+        tokio::spawn(cbc.stream_articles(reqwest::Client::new(), FetchPolicy::default(), HttpCache::disabled(), tx));
         let article = rx.recv().await;
         println!("{:#?}", article.unwrap());
         assert!(rx.recv().await.is_some());
@@ -0,0 +1,92 @@
+use itertools::Itertools;
+use super::newsarticle::NewsArticle;
+
+/// Writes articles as a markdown digest, a list of links under a heading for each source.
+/// Sources come in the order of their first article, so the digest reads like the news list,
+/// and are told apart by url, so two sharing a name get a heading each
+///
+/// Arguments
+/// * title - The heading at the top of the digest
+/// * articles - The articles to list, in order
+pub fn write_digest(title: &str, articles: &[&NewsArticle]) -> String {
+    let mut digest = format!("# {}\n", escape(title));
+    for source in articles.iter().map(|article| &article.source).unique_by(|source| &source.url) {
+        digest.push_str(&format!("\n## {}\n\n", escape(&source.name)));
+        for article in articles.iter().filter(|article| article.source.url == source.url) {
+            let title = match article.title.trim() {
+                "" => article.link.as_str(),
+                title => title,
+            };
+            digest.push_str(&format!("- [{}]({})", escape(title), link_target(&article.link)));
+            if let Some(published) = article.published {
+                digest.push_str(&published.format(" (%Y-%m-%d %H:%M)").to_string());
+            }
+            digest.push('\n');
+        }
+    }
+    digest
+}
+
+/// Backslash escapes the characters markdown would otherwise read as formatting
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Percent encodes the characters which would end a markdown link early
+fn link_target(link: &str) -> String {
+    link.replace(' ', "%20").replace('(', "%28").replace(')', "%29")
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::DateTime;
+    use super::*;
+    use crate::newsroomcore::datasources::DataSources;
+
+    fn article(source: &DataSources, title: &str, link: &str, published: Option<&str>) -> NewsArticle {
+        NewsArticle {
+            title: title.to_string(),
+            published: published.and_then(|date| DateTime::parse_from_rfc3339(date).ok()),
+            ..NewsArticle::test(source, link)
+        }
+    }
+
+    #[test]
+    fn test_write_digest(){
+        let cbc = DataSources::new("cbc", "https://cbc.ca/rss");
+        let cnn = DataSources::new("cnn", "https://cnn.com/rss");
+        let articles = [
+            article(&cbc, "Rates *held* at 5%", "https://cbc.ca/rates", Some("2023-10-03T09:30:00-04:00")),
+            article(&cnn, "[Live] Storm", "https://cnn.com/storm (live)", None),
+            article(&cbc, "", "https://cbc.ca/untitled", None),
+        ];
+        let digest = write_digest("Newsroom digest", &articles.iter().collect::<Vec<_>>());
+        assert_eq!(digest, "# Newsroom digest\n\n\
+            ## cbc\n\n\
+            - [Rates \\*held\\* at 5%](https://cbc.ca/rates) (2023-10-03 09:30)\n\
+            - [https://cbc.ca/untitled](https://cbc.ca/untitled)\n\n\
+            ## cnn\n\n\
+            - [\\[Live\\] Storm](https://cnn.com/storm%20%28live%29)\n");
+
+        // Sources sharing a name still get a heading each
+        let news = DataSources::new("News", "https://a.example.com/rss");
+        let other_news = DataSources::new("News", "https://b.example.com/rss");
+        let articles = [
+            article(&news, "First", "https://a.example.com/1", None),
+            article(&other_news, "Second", "https://b.example.com/2", None),
+        ];
+        let digest = write_digest("Digest", &articles.iter().collect::<Vec<_>>());
+        assert_eq!(digest, "# Digest\n\n\
+            ## News\n\n\
+            - [First](https://a.example.com/1)\n\n\
+            ## News\n\n\
+            - [Second](https://b.example.com/2)\n");
+    }
+}
@@ -0,0 +1,133 @@
+// Code section to check a url a user wants to add as a source, and find the feeds a web page links to
+use std::time::Duration;
+use reqwest::Client;
+use url::Url;
+use super::{fetcherror::FetchError, html::{tokenize, HtmlToken}, newsfetchrss::Feed};
+
+/// Link types which point at a feed we can read
+const FEED_TYPES: [&str; 4] = ["application/rss+xml", "application/atom+xml", "application/feed+json", "application/json"];
+
+/// A feed a web page advertises with a `<link rel="alternate">` tag
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveredFeed {
+    pub title: Option<String>,
+    pub url: String,
+}
+
+/// What we found at a url
+#[derive(Debug, Clone, PartialEq)]
+pub enum Discovery {
+    /// The url is a feed we can read, along with the feed's title
+    Feed(Option<String>),
+    /// The url is a web page, listing these feeds (possibly none)
+    Page(Vec<DiscoveredFeed>),
+}
+
+/// Fetches a url and works out whether it is a feed, or a web page which links to feeds
+///
+/// Arguments
+/// * client - The http client to fetch with
+/// * url - The url to check
+/// * timeout - How long the request may take in total
+pub(crate) async fn discover(client: &Client, url: &str, timeout: Duration) -> Result<Discovery, FetchError> {
+    let response = client.get(url).timeout(timeout).send().await?.error_for_status()?;
+    // Redirects are followed, so relative links are resolved against wherever we ended up
+    let base = response.url().clone();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+    let content = response.bytes().await?;
+
+    match Feed::parse(&content, content_type.as_deref()) {
+        Ok(feed) => Ok(Discovery::Feed(feed.title())),
+        Err(e) => {
+            let text = String::from_utf8_lossy(&content);
+            let is_html = content_type.as_deref().is_some_and(|mime| mime.contains("html"))
+                || text.trim_start().get(..15).is_some_and(|start| start.to_lowercase().starts_with("<!doctype html"))
+                || text.to_lowercase().contains("<html");
+            // Content which isn't even markup or JSON is more helpfully described as not a feed than as a malformed one
+            let looks_structured = text.trim_start().starts_with(['<', '{']);
+            match (is_html, looks_structured) {
+                (true, _) => Ok(Discovery::Page(feed_links(&text, &base))),
+                (false, true) => Err(e),
+                (false, false) => Err(FetchError::NotAFeed),
+            }
+        },
+    }
+}
+
+/// Finds the feeds a web page advertises in its `<link rel="alternate">` tags
+///
+/// Arguments
+/// * html - The web page
+/// * base - The url of the page, which relative links are resolved against
+pub fn feed_links(html: &str, base: &Url) -> Vec<DiscoveredFeed> {
+    let mut feeds: Vec<DiscoveredFeed> = vec![];
+    for token in tokenize(html) {
+        let HtmlToken::Start { name, .. } = &token else { continue };
+        if name != "link" {
+            continue;
+        }
+        let is_alternate = token
+            .attr("rel")
+            .is_some_and(|rel| rel.split_whitespace().any(|rel| rel.eq_ignore_ascii_case("alternate")));
+        let is_feed = token
+            .attr("type")
+            .is_some_and(|link_type| FEED_TYPES.contains(&link_type.trim().to_lowercase().as_str()));
+        let url = token.attr("href").and_then(|href| base.join(href.trim()).ok());
+        if let (true, true, Some(url)) = (is_alternate, is_feed, url) {
+            let url = url.to_string();
+            if !feeds.iter().any(|feed| feed.url == url) {
+                let title = token.attr("title").map(|title| title.trim().to_string()).filter(|title| !title.is_empty());
+                feeds.push(DiscoveredFeed { title, url });
+            }
+        }
+    }
+    feeds
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::newsroomcore::stubserver::{StubServer, StubResponse};
+
+    const HOMEPAGE: &str = r#"<!DOCTYPE html>
+        <html><head>
+            <title>Example</title>
+            <link rel="stylesheet" href="/style.css">
+            <link rel="alternate" type="application/rss+xml" title="Top stories" href="/feed.rss">
+            <LINK REL="alternate" TYPE="application/atom+xml" HREF="https://other.example.com/atom.xml">
+            <link rel="alternate" type="application/rss+xml" title="Duplicate" href="feed.rss">
+            <link rel="alternate" hreflang="fr" href="/fr/">
+        </head><body><p>Welcome</p></body></html>"#;
+
+    #[test]
+    fn test_feed_links(){
+        let feeds = feed_links(HOMEPAGE, &Url::parse("https://example.com/").unwrap());
+        assert_eq!(feeds, vec![
+            DiscoveredFeed { title: Some("Top stories".to_string()), url: "https://example.com/feed.rss".to_string() },
+            DiscoveredFeed { title: None, url: "https://other.example.com/atom.xml".to_string() },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_discover(){
+        let server = StubServer::start(vec![
+            ("/", vec![StubResponse::ok(HOMEPAGE).header("Content-Type", "text/html")]),
+            ("/feed.rss", vec![StubResponse::ok(include_str!("../../resources/fixtures/rss.xml"))]),
+            ("/plain", vec![StubResponse::ok("Just some text").header("Content-Type", "text/plain")]),
+        ]).await;
+        let client = Client::new();
+        let timeout = Duration::from_secs(1);
+
+        assert_eq!(discover(&client, &server.url("/feed.rss"), timeout).await, Ok(Discovery::Feed(Some("Newsroom Test Channel".to_string()))));
+        let Ok(Discovery::Page(feeds)) = discover(&client, &server.url("/"), timeout).await else {
+            panic!("the homepage should be read as a web page")
+        };
+        assert_eq!(feeds[0].url, server.url("/feed.rss"));
+        assert_eq!(discover(&client, &server.url("/plain"), timeout).await, Err(FetchError::NotAFeed));
+        assert_eq!(discover(&client, &server.url("/missing"), timeout).await, Err(FetchError::Http(404)));
+    }
+}
@@ -0,0 +1,79 @@
+use std::{error::Error, fmt};
+
+/// Reasons fetching a source can fail, kept distinct so the user can tell what went wrong
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchError {
+    /// The server could not be reached (DNS failure, connection refused)
+    Connect(String),
+    /// A secure connection couldn't be set up, usually because the server's certificate is invalid
+    Tls(String),
+    /// The server took too long to respond
+    Timeout,
+    /// The server responded with a non success status code
+    Http(u16),
+    /// The response body could not be read
+    Body(String),
+    /// The document isn't RSS, Atom or JSON Feed at all
+    NotAFeed,
+    /// The document looks like a feed but is malformed
+    Parse(String),
+    /// A linked page was fetched but no story could be found on it
+    NoArticle,
+}
+
+impl FetchError {
+    /// Whether trying again might help. Client errors and bad documents won't fix themselves,
+    /// but dropped connections, timeouts, rate limits and server errors often do
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            FetchError::Connect(_) | FetchError::Timeout | FetchError::Body(_) => true,
+            FetchError::Http(status) => *status == 429 || *status >= 500,
+            FetchError::Tls(_) | FetchError::NotAFeed | FetchError::Parse(_) | FetchError::NoArticle => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            FetchError::Timeout
+        } else if let Some(status) = e.status() {
+            FetchError::Http(status.as_u16())
+        } else if e.is_body() || e.is_decode() {
+            FetchError::Body(e.to_string())
+        } else {
+            // Dig down to the root cause, reqwest's own message is just "error sending request"
+            let mut source: &dyn Error = &e;
+            let mut is_tls = false;
+            while let Some(inner) = source.source() {
+                source = inner;
+                let message = source.to_string().to_lowercase();
+                is_tls |= ["certificate", "ssl", "tls", "handshake"].iter().any(|word| message.contains(word));
+            }
+            match is_tls {
+                true => FetchError::Tls(source.to_string()),
+                false => FetchError::Connect(source.to_string()),
+            }
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Connect(reason) => write!(f, "could not connect: {}", reason),
+            FetchError::Tls(reason) => write!(f, "could not make a secure connection: {}", reason),
+            FetchError::Timeout => write!(f, "timed out"),
+            FetchError::Http(status) => match reqwest::StatusCode::from_u16(*status).ok().and_then(|s| s.canonical_reason()) {
+                Some(reason) => write!(f, "HTTP {} {}", status, reason),
+                None => write!(f, "HTTP {}", status),
+            },
+            FetchError::Body(reason) => write!(f, "could not read response: {}", reason),
+            FetchError::NotAFeed => write!(f, "not an RSS, Atom or JSON feed"),
+            FetchError::Parse(reason) => write!(f, "malformed feed: {}", reason),
+            FetchError::NoArticle => write!(f, "no article found on the page"),
+        }
+    }
+}
+
+impl Error for FetchError {}
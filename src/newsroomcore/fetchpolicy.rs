@@ -0,0 +1,83 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use super::datasources::DataSources;
+
+/// Controls how patiently we fetch feeds, read from the [fetch] table of newsroom.toml
+/// Individual sources can override the timeout and retry count
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FetchPolicy {
+    /// How long a single request may take before it is abandoned
+    pub timeout_secs: u64,
+    /// How many times a failed request is retried
+    pub retries: u32,
+    /// How long to wait before the first retry, doubling for each retry after that
+    pub backoff_ms: u64,
+    /// How long the whole fetch may take, sources still loading after this are reported as timed out
+    pub deadline_secs: u64,
+}
+
+impl Default for FetchPolicy {
+    fn default() -> Self {
+        FetchPolicy {
+            timeout_secs: 10,
+            retries: 2,
+            backoff_ms: 500,
+            deadline_secs: 30,
+        }
+    }
+}
+
+impl FetchPolicy {
+    /// The policy to use for a particular source, with any of its overrides applied
+    ///
+    /// Arguments
+    /// * source - The source being fetched
+    pub fn for_source(&self, source: &DataSources) -> FetchPolicy {
+        FetchPolicy {
+            timeout_secs: source.timeout_secs.unwrap_or(self.timeout_secs),
+            retries: source.retries.unwrap_or(self.retries),
+            ..self.clone()
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    pub fn deadline(&self) -> Duration {
+        Duration::from_secs(self.deadline_secs)
+    }
+
+    /// How long to wait before making the given retry, starting from retry 0
+    pub fn backoff(&self, retry: u32) -> Duration {
+        Duration::from_millis(self.backoff_ms.saturating_mul(2u64.saturating_pow(retry)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_source_overrides(){
+        let policy = FetchPolicy::default();
+        let mut source = DataSources::new("slow", "https://example.com/feed");
+        assert_eq!(policy.for_source(&source), policy);
+
+        source.timeout_secs = Some(60);
+        source.retries = Some(0);
+        let overridden = policy.for_source(&source);
+        assert_eq!(overridden.timeout(), Duration::from_secs(60));
+        assert_eq!(overridden.retries, 0);
+        assert_eq!(overridden.deadline_secs, policy.deadline_secs);
+    }
+
+    #[test]
+    fn test_backoff_doubles(){
+        let policy = FetchPolicy { backoff_ms: 100, ..FetchPolicy::default() };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+    }
+}
@@ -0,0 +1,17 @@
+use std::time::Duration;
+use super::fetcherror::FetchError;
+
+/// Progress of fetching a single source, reported to the app as each source finishes
+#[derive(Debug, Clone)]
+pub enum SourceStatus {
+    Loading,
+    Loaded { articles: usize, elapsed: Duration },
+    Failed { error: FetchError, elapsed: Duration },
+}
+
+impl SourceStatus {
+    /// Whether the source has finished, successfully or not
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, SourceStatus::Loading)
+    }
+}
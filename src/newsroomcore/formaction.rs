@@ -0,0 +1,20 @@
+use super::{datasources::DataSources, discovery::DiscoveredFeed};
+
+/// What submitting a form does
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormAction {
+    ImportOpml,
+    ExportOpml,
+    AddSource,
+    /// Edit the source at the given index
+    EditSource(usize),
+    /// Confirm deleting the source at the given index
+    DeleteSource(usize),
+    /// Pick which of the feeds found on a web page to add, in place of the page itself
+    ChooseFeed {
+        source: DataSources,
+        /// The index of the source being edited, None when adding a source
+        replacing: Option<usize>,
+        feeds: Vec<DiscoveredFeed>,
+    },
+}
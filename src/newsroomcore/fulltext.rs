@@ -0,0 +1,92 @@
+// Code section to fetch the full story behind an article's link, for feeds which only carry a teaser
+use std::{fs, path::PathBuf, time::Duration};
+use log::{info, warn};
+use reqwest::Client;
+use super::{fetcherror::FetchError, readability::extract, storage::stable_hash};
+
+/// Remembers the story extracted from each article's page, so each page is only downloaded once
+#[derive(Debug, Clone)]
+pub struct FullTextCache {
+    dir: Option<PathBuf>,
+}
+
+impl FullTextCache {
+    /// Creates a cache which stores stories in the given directory
+    pub fn new(dir: PathBuf) -> FullTextCache {
+        FullTextCache { dir: Some(dir) }
+    }
+
+    /// Creates a cache which never stores anything, every story is downloaded again
+    pub fn disabled() -> FullTextCache {
+        FullTextCache { dir: None }
+    }
+
+    fn path(&self, link: &str) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        Some(dir.join(format!("{:016x}.html", stable_hash(link))))
+    }
+
+    /// Loads the story we previously extracted from a page, if any
+    pub(crate) fn load(&self, link: &str) -> Option<String> {
+        fs::read_to_string(self.path(link)?).ok()
+    }
+
+    /// Remembers the story extracted from a page
+    pub(crate) fn store(&self, link: &str, story: &str) {
+        let Some(path) = self.path(link) else { return };
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, story));
+        if let Err(e) = result {
+            warn!("Couldn't cache the full text of {}: {}", link, e);
+        }
+    }
+}
+
+/// Fetches the page an article links to and extracts its story as simplified HTML, reusing the cached story if we have one
+///
+/// Arguments
+/// * client - The http client to fetch with
+/// * link - The article's link
+/// * timeout - How long the request may take in total
+/// * cache - Where previously extracted stories are remembered
+pub(crate) async fn fetch_full_text(client: &Client, link: &str, timeout: Duration, cache: &FullTextCache) -> Result<String, FetchError> {
+    if let Some(story) = cache.load(link) {
+        info!("Reusing cached full text of {}", link);
+        return Ok(story);
+    }
+
+    let page = client.get(link).timeout(timeout).send().await?.error_for_status()?.text().await?;
+    let story = extract(&page).ok_or(FetchError::NoArticle)?;
+    cache.store(link, &story);
+    Ok(story)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::newsroomcore::{storage::TempPath, stubserver::{StubServer, StubResponse}};
+
+    #[tokio::test]
+    async fn test_fetch_full_text(){
+        let server = StubServer::start(vec![
+            ("/story", vec![StubResponse::ok(include_str!("../../resources/fixtures/article.html")).header("Content-Type", "text/html; charset=utf-8")]),
+            ("/empty", vec![StubResponse::ok("<html><body><a href='/'>Home</a></body></html>")]),
+        ]).await;
+        let dir = TempPath::new("fulltext");
+        let cache = FullTextCache::new(dir.to_path_buf());
+        let client = Client::new();
+
+        let story = fetch_full_text(&client, &server.url("/story"), Duration::from_secs(1), &cache).await.unwrap();
+        assert!(story.contains("The council voted on Tuesday"));
+
+        // The second read comes from the cache
+        let cached = fetch_full_text(&client, &server.url("/story"), Duration::from_secs(1), &cache).await.unwrap();
+        assert_eq!(cached, story);
+        assert_eq!(server.requests().len(), 1);
+
+        assert_eq!(fetch_full_text(&client, &server.url("/empty"), Duration::from_secs(1), &cache).await, Err(FetchError::NoArticle));
+        assert_eq!(fetch_full_text(&client, &server.url("/missing"), Duration::from_secs(1), &cache).await, Err(FetchError::Http(404)));
+    }
+}
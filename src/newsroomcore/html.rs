@@ -0,0 +1,296 @@
+// Code section to pick apart the HTML that feeds embed in their articles
+// Feeds only need a forgiving tokenizer, not a full HTML5 parser, so anything unexpected is treated as text
+use std::{borrow::Cow, sync::OnceLock};
+use entities::ENTITIES;
+
+/// A piece of an HTML document
+#[derive(Debug, Clone, PartialEq)]
+pub enum HtmlToken {
+    /// An opening tag, names are lowercased and attribute values have their entities decoded
+    Start { name: String, attrs: Vec<(String, String)>, self_closing: bool },
+    /// A closing tag
+    End(String),
+    /// Text between tags, with entities decoded
+    Text(String),
+}
+
+impl HtmlToken {
+    /// Looks up an attribute of a start tag
+    pub fn attr(&self, attr: &str) -> Option<&str> {
+        match self {
+            HtmlToken::Start { attrs, .. } => attrs
+                .iter()
+                .find(|(name, _)| name == attr)
+                .map(|(_, value)| value.as_str()),
+            _ => None,
+        }
+    }
+}
+
+/// Elements whose content is never markup, so their text is kept raw until the closing tag
+const RAW_TEXT: [&str; 2] = ["script", "style"];
+
+/// Splits an HTML document into tags and text. Comments and doctypes are dropped
+///
+/// Arguments
+/// * html - The document or fragment to split
+pub fn tokenize(html: &str) -> Vec<HtmlToken> {
+    let mut tokens = vec![];
+    let mut rest = html;
+    while !rest.is_empty() {
+        let Some(open) = rest.find('<') else {
+            tokens.push(HtmlToken::Text(decode_entities(rest)));
+            break;
+        };
+        if open > 0 {
+            tokens.push(HtmlToken::Text(decode_entities(&rest[..open])));
+            rest = &rest[open..];
+        }
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else if let Some((token, remaining)) = parse_tag(rest) {
+            rest = remaining;
+            // Keep the content of raw text elements together rather than reading it as markup
+            if let HtmlToken::Start { name, self_closing: false, .. } = &token {
+                if RAW_TEXT.contains(&name.as_str()) {
+                    let name = name.clone();
+                    let close = find_ignore_case(rest, &format!("</{}", name)).unwrap_or(rest.len());
+                    let raw = rest[..close].to_string();
+                    rest = &rest[close..];
+                    rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+                    tokens.push(token);
+                    tokens.push(HtmlToken::Text(raw));
+                    tokens.push(HtmlToken::End(name));
+                    continue;
+                }
+            }
+            tokens.push(token);
+        } else {
+            // A stray '<' which doesn't start a tag
+            tokens.push(HtmlToken::Text("<".to_string()));
+            rest = &rest[1..];
+        }
+    }
+    tokens
+}
+
+/// Reads the tag at the start of the input, returning it and whatever follows it
+fn parse_tag(input: &str) -> Option<(HtmlToken, &str)> {
+    let end = input.find('>')?;
+    let inner = &input[1..end];
+    let rest = &input[end + 1..];
+
+    if let Some(name) = inner.strip_prefix('/') {
+        let name = name.trim().to_lowercase();
+        return is_tag_name(&name).then_some((HtmlToken::End(name), rest));
+    }
+
+    let self_closing = inner.ends_with('/');
+    let inner = inner.trim_end_matches('/');
+    let name_end = inner.find(|c: char| c.is_whitespace()).unwrap_or(inner.len());
+    let name = inner[..name_end].to_lowercase();
+    if !is_tag_name(&name) {
+        return None;
+    }
+    let attrs = parse_attrs(&inner[name_end..]);
+    Some((HtmlToken::Start { name, attrs, self_closing }, rest))
+}
+
+fn is_tag_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == ':')
+}
+
+/// Reads `name="value"`, `name='value'`, `name=value` and bare `name` attributes
+fn parse_attrs(input: &str) -> Vec<(String, String)> {
+    let mut attrs = vec![];
+    let mut rest = input.trim_start();
+    while !rest.is_empty() {
+        let name_end = rest.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(rest.len());
+        let name = rest[..name_end].to_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let value = match rest.strip_prefix('=') {
+            Some(after) => {
+                let after = after.trim_start();
+                let (value, remaining) = match after.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let close = after[1..].find(quote).map_or(after.len(), |i| i + 1);
+                        (&after[1..close], after.get(close + 1..).unwrap_or(""))
+                    },
+                    _ => {
+                        let close = after.find(char::is_whitespace).unwrap_or(after.len());
+                        (&after[..close], &after[close..])
+                    },
+                };
+                rest = remaining.trim_start();
+                decode_entities(value)
+            },
+            None => String::new(),
+        };
+        if !name.is_empty() {
+            attrs.push((name, value));
+        }
+    }
+    attrs
+}
+
+/// Tags which sit inside a word or sentence, so dropping them shouldn't separate the text either side
+const INLINE: [&str; 14] = ["a", "b", "strong", "i", "em", "u", "s", "span", "code", "small", "mark", "abbr", "sub", "sup"];
+
+/// The text a reader would see in an HTML fragment, without its tags, scripts or styles.
+/// Other tags become spaces, so words either side of a `<br>` or `</p>` stay apart
+///
+/// Arguments
+/// * html - The fragment to read
+pub fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut raw = false;
+    for token in tokenize(html) {
+        match token {
+            HtmlToken::Text(chunk) if !raw => text.push_str(&chunk),
+            HtmlToken::Text(_) => {},
+            HtmlToken::Start { name, .. } | HtmlToken::End(name) if RAW_TEXT.contains(&name.as_str()) => raw = !raw,
+            HtmlToken::Start { name, .. } | HtmlToken::End(name) if !INLINE.contains(&name.as_str()) => text.push(' '),
+            HtmlToken::Start { .. } | HtmlToken::End(_) => {},
+        }
+    }
+    text
+}
+
+/// Finds where the needle starts in the haystack, ignoring ASCII case
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .char_indices()
+        .map(|(index, _)| index)
+        .find(|&index| haystack[index..].get(..needle.len()).is_some_and(|candidate| candidate.eq_ignore_ascii_case(needle)))
+}
+
+/// Replaces character references like `&amp;`, `&#8217;` and `&#x2014;` with the characters they stand for.
+/// Unknown references are left as they are
+///
+/// Arguments
+/// * text - The text to decode
+pub fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let reference = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 32)
+            .map(|end| &rest[1..end + 1])
+            .and_then(|name| decode_entity(name).map(|text| (text, name.len() + 2)));
+        match reference {
+            Some((text, length)) => {
+                decoded.push_str(&text);
+                rest = &rest[length..];
+            },
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// The HTML5 entities, by name without the `&` and `;`, along with the text they stand for. Sorted by name
+/// so they can be binary searched, which the table in the entities crate isn't
+fn entity_table() -> &'static [(&'static str, &'static str)] {
+    static TABLE: OnceLock<Vec<(&str, &str)>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table: Vec<(&str, &str)> = ENTITIES
+            .iter()
+            .filter_map(|entity| Some((entity.entity.strip_prefix('&')?.strip_suffix(';')?, entity.characters)))
+            .collect();
+        table.sort_unstable_by_key(|(name, _)| *name);
+        table
+    })
+}
+
+/// The text a single reference (without the `&` and `;`) stands for, looked up in the HTML5 entity table
+fn decode_entity(name: &str) -> Option<Cow<'static, str>> {
+    let Some(number) = name.strip_prefix('#') else {
+        let table = entity_table();
+        return table
+            .binary_search_by(|(candidate, _)| candidate.cmp(&name))
+            .ok()
+            .map(|index| Cow::Borrowed(table[index].1));
+    };
+    let (digits, radix) = match number.strip_prefix(['x', 'X']) {
+        Some(hex) => (hex, 16),
+        None => (number, 10),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    // Like browsers, NUL, surrogates and numbers past the last code point stand for the replacement character
+    let c = u32::from_str_radix(digits, radix)
+        .ok()
+        .filter(|&code| code != 0)
+        .and_then(char::from_u32)
+        .unwrap_or(char::REPLACEMENT_CHARACTER);
+    Some(Cow::Owned(c.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn start(name: &str, attrs: &[(&str, &str)]) -> HtmlToken {
+        HtmlToken::Start {
+            name: name.to_string(),
+            attrs: attrs.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect(),
+            self_closing: false,
+        }
+    }
+
+    #[test]
+    fn test_tokenize(){
+        let tokens = tokenize(r#"<!-- note --><P class=lead>Fish &amp; chips<A HREF='/menu?a=1&amp;b=2' target="_blank">menu</a></p>"#);
+        assert_eq!(tokens, vec![
+            start("p", &[("class", "lead")]),
+            HtmlToken::Text("Fish & chips".to_string()),
+            start("a", &[("href", "/menu?a=1&b=2"), ("target", "_blank")]),
+            HtmlToken::Text("menu".to_string()),
+            HtmlToken::End("a".to_string()),
+            HtmlToken::End("p".to_string()),
+        ]);
+        assert_eq!(tokens[2].attr("href"), Some("/menu?a=1&b=2"));
+    }
+
+    #[test]
+    fn test_raw_text_and_strays(){
+        let tokens = tokenize("<script>if (a < b) { x = '<p>'; }</SCRIPT>1 < 2<br/>");
+        assert_eq!(tokens, vec![
+            start("script", &[]),
+            HtmlToken::Text("if (a < b) { x = '<p>'; }".to_string()),
+            HtmlToken::End("script".to_string()),
+            HtmlToken::Text("1 ".to_string()),
+            HtmlToken::Text("<".to_string()),
+            HtmlToken::Text(" 2".to_string()),
+            HtmlToken::Start { name: "br".to_string(), attrs: vec![], self_closing: true },
+        ]);
+    }
+
+    #[test]
+    fn test_plain_text(){
+        assert_eq!(plain_text(r#"<p class="lead">Fish &amp; <b>ch</b>ips</p><script>var p = "<p>";</script>menu<br/>here"#),  " Fish & chips menu here");
+    }
+
+    #[test]
+    fn test_decode_entities(){
+        assert_eq!(decode_entities("Tom &amp; Jerry&#39;s &#x2014; &lt;b&gt;"), "Tom & Jerry's \u{2014} <b>");
+        assert_eq!(decode_entities("AT&T &unknown; & done"), "AT&T &unknown; & done");
+        assert_eq!(decode_entities("&ldquo;Caf&eacute;&rdquo; &mdash; 5&nbsp;&euro;&hellip;"), "“Café” — 5\u{a0}€…");
+        assert_eq!(decode_entities("&frac34; &hearts; &Omega; &NotEqualTilde;"), "¾ ♥ Ω \u{2242}\u{338}");
+        assert_eq!(decode_entities("&#0;&#xD800;&#xdfff;&#x110000;&#99999999999;"), "\u{FFFD}".repeat(5));
+        assert_eq!(decode_entities("&#; &#x; &#12a;"), "&#; &#x; &#12a;");
+        assert!(entity_table().windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+}
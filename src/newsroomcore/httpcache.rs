@@ -0,0 +1,62 @@
+use std::{error::Error, fs, path::PathBuf};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use super::storage::{stable_hash, read_json, write_json};
+
+/// Remembers the validators and body of the last successful response from each feed,
+/// so refreshes can ask servers to only send feeds which have changed since
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    dir: Option<PathBuf>,
+}
+
+/// The validators a server sent along with a feed
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub(crate) struct CachedResponse {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub content_type: Option<String>,
+}
+
+impl HttpCache {
+    /// Creates a cache which stores responses in the given directory
+    pub fn new(dir: PathBuf) -> HttpCache {
+        HttpCache { dir: Some(dir) }
+    }
+
+    /// Creates a cache which never stores anything, every fetch downloads the full feed
+    pub fn disabled() -> HttpCache {
+        HttpCache { dir: None }
+    }
+
+    /// The files holding the validators and body cached for a url
+    fn paths(&self, url: &str) -> Option<(PathBuf, PathBuf)> {
+        let dir = self.dir.as_ref()?;
+        let name = format!("{:016x}", stable_hash(url));
+        Some((dir.join(format!("{}.json", name)), dir.join(format!("{}.body", name))))
+    }
+
+    /// Loads the validators and body we last received from a url, if any
+    pub(crate) fn load(&self, url: &str) -> Option<(CachedResponse, Vec<u8>)> {
+        let (meta_path, body_path) = self.paths(url)?;
+        let response = read_json(&meta_path)?;
+        let body = fs::read(body_path).ok()?;
+        Some((response, body))
+    }
+
+    /// Remembers a response so the next fetch of the url can be conditional
+    pub(crate) fn store(&self, url: &str, response: &CachedResponse, body: &[u8]) {
+        let Some((meta_path, body_path)) = self.paths(url) else { return };
+        // Write the body first so the validators never point at a missing body
+        let write_body = || -> Result<(), Box<dyn Error>> {
+            if let Some(parent) = body_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            Ok(fs::write(&body_path, body)?)
+        };
+        let result = write_body().and_then(|_| write_json(&meta_path, response));
+        if let Err(e) = result {
+            warn!("Couldn't cache response from {}: {}", url, e);
+        }
+    }
+}
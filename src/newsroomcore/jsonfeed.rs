@@ -0,0 +1,168 @@
+// Code section to read JSON Feed (https://jsonfeed.org) documents
+use chrono::DateTime;
+use serde::Deserialize;
+use super::{newsarticle::NewsArticle, datasources::DataSources, fetcherror::FetchError};
+
+/// Prefix shared by the version urls of every JSON Feed release
+const VERSION_PREFIX: &str = "https://jsonfeed.org/version/";
+
+/// The subset of a JSON Feed 1.0 / 1.1 document that we care about
+#[derive(Debug, Deserialize)]
+pub(crate) struct JsonFeed {
+    pub version: String,
+    pub title: Option<String>,
+    #[serde(default)]
+    pub authors: Vec<JsonFeedAuthor>,
+    /// Deprecated in 1.1 in favour of `authors`, still common in the wild
+    pub author: Option<JsonFeedAuthor>,
+    #[serde(default)]
+    pub items: Vec<JsonFeedItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct JsonFeedItem {
+    /// Required by the spec to be a string, but numbers are common in the wild
+    pub id: Option<serde_json::Value>,
+    pub url: Option<String>,
+    pub external_url: Option<String>,
+    pub title: Option<String>,
+    pub content_html: Option<String>,
+    pub content_text: Option<String>,
+    pub summary: Option<String>,
+    pub date_published: Option<String>,
+    #[serde(default)]
+    pub authors: Vec<JsonFeedAuthor>,
+    pub author: Option<JsonFeedAuthor>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct JsonFeedAuthor {
+    pub name: Option<String>,
+}
+
+impl JsonFeed {
+    /// Parses a JSON Feed document, rejecting JSON that isn't a JSON Feed
+    ///
+    /// Arguments
+    /// * content - The raw bytes of the feed document
+    pub(crate) fn read_from(content: &[u8]) -> Result<JsonFeed, FetchError> {
+        let feed: JsonFeed = serde_json::from_slice(content).map_err(|e| FetchError::Parse(e.to_string()))?;
+        if feed.version.starts_with(VERSION_PREFIX) {
+            Ok(feed)
+        } else {
+            Err(FetchError::NotAFeed)
+        }
+    }
+
+    /// Converts every item in the feed into a news article
+    ///
+    /// Arguments
+    /// * source - The source the feed was fetched from
+    pub(crate) fn articles(&self, source: &DataSources) -> Vec<NewsArticle> {
+        self.items
+            .iter()
+            .map(|item| item.to_article(self, source))
+            .collect()
+    }
+}
+
+impl JsonFeedItem {
+    /// Converts the item into a news article, falling back to the feed authors if the item has none
+    fn to_article(&self, feed: &JsonFeed, source: &DataSources) -> NewsArticle {
+        let authors = match author_names(&self.authors, &self.author) {
+            names if names.is_empty() => author_names(&feed.authors, &feed.author),
+            names => names,
+        };
+        let authors = if authors.is_empty() { vec!["".to_string()] } else { authors };
+
+        let title = self.title.clone().unwrap_or_default();
+
+        let summary = self
+            .content_html
+            .as_ref()
+            .or(self.content_text.as_ref())
+            .or(self.summary.as_ref())
+            .cloned()
+            .unwrap_or_default();
+
+        let content = self
+            .content_html
+            .as_ref()
+            .or(self.content_text.as_ref())
+            .cloned();
+
+        let link = self
+            .url
+            .as_ref()
+            .or(self.external_url.as_ref())
+            .cloned()
+            .unwrap_or_default();
+
+        let id = match &self.id {
+            Some(serde_json::Value::String(id)) if !id.is_empty() => id.clone(),
+            Some(serde_json::Value::Number(id)) => id.to_string(),
+            _ => NewsArticle::id_from_link(&link),
+        };
+
+        let published = self
+            .date_published
+            .as_ref()
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok());
+
+        NewsArticle{ id, authors, title, summary, content, link, source: source.clone(), published }
+    }
+}
+
+/// Collects the names of a 1.1 `authors` list, or the 1.0 `author` object if the list is empty
+fn author_names(authors: &[JsonFeedAuthor], author: &Option<JsonFeedAuthor>) -> Vec<String> {
+    let names: Vec<String> = authors.iter().filter_map(|a| a.name.clone()).collect();
+    if names.is_empty() {
+        author.iter().filter_map(|a| a.name.clone()).collect()
+    } else {
+        names
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fixture_source() -> DataSources {
+        DataSources::new("fixture", "https://example.com/feed.json")
+    }
+
+    #[test]
+    fn test_parse_json_feed(){
+        let feed = JsonFeed::read_from(include_bytes!("../../resources/fixtures/feed.json")).unwrap();
+
+        let articles = feed.articles(&fixture_source());
+        assert_eq!(articles.len(), 3);
+
+        // Html content is preferred, nameless authors are dropped, dates keep their offset
+        assert_eq!(articles[0].title, "JSON headline");
+        assert_eq!(articles[0].summary, "<p>Rendered <em>story</em></p>");
+        assert_eq!(articles[0].link, "https://example.com/json-first");
+        assert_eq!(articles[0].authors, vec!["Jane Reporter"]);
+        assert_eq!(
+            articles[0].published,
+            Some(DateTime::parse_from_rfc3339("2023-10-03T18:30:00Z").unwrap())
+        );
+
+        // Text content and external urls fill in, and feed authors are inherited
+        assert_eq!(articles[1].summary, "Plain text only");
+        assert_eq!(articles[1].link, "https://elsewhere.example.com/linked");
+        assert_eq!(articles[1].authors, vec!["Feed Maintainer"]);
+        assert_eq!(articles[1].id, "2");
+
+        // Missing titles and unreadable dates don't drop the item
+        assert_eq!(articles[2].title, "");
+        assert_eq!(articles[2].summary, "A title-less micro post");
+        assert_eq!(articles[2].published, None);
+    }
+
+    #[test]
+    fn test_reject_other_json(){
+        assert_eq!(JsonFeed::read_from(br#"{"version": "1.0", "items": []}"#).unwrap_err(), FetchError::NotAFeed);
+        assert!(JsonFeed::read_from(b"[1, 2, 3]").is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use super::{html::plain_text, newsarticle::NewsArticle};
+
+/// Narrows the news list down to the articles the user is looking for
+#[derive(Debug, Clone, Default)]
+pub struct ListFilter {
+    /// Text to search for in titles, summaries and source names, ignoring case
+    pub query: String,
+    /// Whether the search line is open and taking input
+    pub editing: bool,
+    /// Only list articles from the source with this url
+    pub source_url: Option<String>,
+}
+
+impl ListFilter {
+    /// Whether a search is open or narrowing the list
+    pub fn is_active(&self) -> bool {
+        self.editing || !self.query.is_empty()
+    }
+
+    /// Whether the article should be listed
+    ///
+    /// Arguments
+    /// * article - The article to check
+    pub fn matches(&self, article: &NewsArticle) -> bool {
+        let from_source = match &self.source_url {
+            Some(url) => article.source.url == *url,
+            None => true,
+        };
+        from_source && self.matches_query(article)
+    }
+
+    // Summaries are HTML, so only their text is searched, otherwise "p" or "href" would match nearly everything
+    fn matches_query(&self, article: &NewsArticle) -> bool {
+        self.query.is_empty()
+            || contains(&article.title, &self.query)
+            || contains(&article.source.name, &self.query)
+            || contains(&plain_text(&article.summary), &self.query)
+    }
+
+    /// The indices of the articles that should be listed, in order
+    ///
+    /// Arguments
+    /// * articles - Every article that could be listed
+    pub fn visible(&self, articles: &[NewsArticle]) -> Vec<usize> {
+        articles
+            .iter()
+            .enumerate()
+            .filter(|(_, article)| self.matches(article))
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+/// Whether the text contains the query, ignoring case
+fn contains(text: &str, query: &str) -> bool {
+    !match_ranges(text, query).is_empty()
+}
+
+/// Finds every place the query appears in the text, ignoring case,
+/// as char ranges so they can be used to split the text for highlighting
+///
+/// Arguments
+/// * text - The text to search
+/// * query - The text to search for
+pub fn match_ranges(text: &str, query: &str) -> Vec<(usize, usize)> {
+    let fold = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let text: Vec<char> = text.chars().map(fold).collect();
+    let query: Vec<char> = query.chars().map(fold).collect();
+    if query.is_empty() {
+        return vec![];
+    }
+
+    let mut ranges = vec![];
+    let mut start = 0;
+    while start + query.len() <= text.len() {
+        if text[start..start + query.len()] == query[..] {
+            ranges.push((start, start + query.len()));
+            start += query.len();
+        } else {
+            start += 1;
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::newsroomcore::datasources::DataSources;
+
+    #[test]
+    fn test_match_ranges(){
+        assert_eq!(match_ranges("Bank of Canada holds rates", "can"), vec![(8, 11)]);
+        assert_eq!(match_ranges("Ééé", "é"), vec![(0, 1), (1, 2), (2, 3)]);
+        assert!(match_ranges("anything", "").is_empty());
+    }
+
+    #[test]
+    fn test_filter_fields(){
+        let article = |title: &str, summary: &str, source: &str| NewsArticle {
+            summary: summary.to_string(),
+            ..NewsArticle::test(&DataSources::new(source, &format!("https://{}.example.com", source)), title)
+        };
+        let articles = vec![
+            article("Rates hold", "The bank of Canada", "cbc"),
+            article("Election night", "Polls close", "CNN"),
+            article("Weather", "Sunny", "globe"),
+        ];
+        let mut filter = ListFilter::default();
+        assert_eq!(filter.visible(&articles), vec![0, 1, 2]);
+
+        filter.query = "CANADA".to_string();
+        assert_eq!(filter.visible(&articles), vec![0]);
+        filter.query = "cnn".to_string();
+        assert_eq!(filter.visible(&articles), vec![1]);
+        filter.query = "o".to_string();
+        assert_eq!(filter.visible(&articles), vec![0, 1, 2]);
+
+        // Markup in a summary isn't searched, only the text it shows
+        let linked = vec![article("Budget", r#"<p>Read the <a href="https://example.com/plan" class="x">plan</a></p>"#, "cbc")];
+        filter.query = "href".to_string();
+        assert!(filter.visible(&linked).is_empty());
+        filter.query = "the plan".to_string();
+        assert_eq!(filter.visible(&linked), vec![0]);
+        filter.query = "o".to_string();
+
+        // The source filter narrows the search further
+        filter.source_url = Some(articles[2].source.url.clone());
+        assert_eq!(filter.visible(&articles), vec![2]);
+    }
+}
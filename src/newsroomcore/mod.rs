@@ -1,4 +1,24 @@
 pub mod newsarticle;
 pub mod newsfetchrss;
+pub mod jsonfeed;
+pub mod fetcherror;
+pub mod fetchstatus;
+pub mod fetchpolicy;
+pub mod httpcache;
+pub mod articlestore;
+pub mod readarticles;
+pub mod savedarticles;
+pub mod sortmode;
+pub mod listfilter;
+pub mod html;
+pub mod readability;
+pub mod fulltext;
+pub mod opml;
+pub mod digest;
+pub mod discovery;
+pub mod formaction;
+pub(crate) mod storage;
 pub mod newsroomstate;
-pub mod datasources;
\ No newline at end of file
+pub mod datasources;
+#[cfg(test)]
+pub(crate) mod stubserver;
@@ -1,11 +1,138 @@
-use crate::newsroomcore::datasources::DataSources;
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use crate::newsroomcore::{datasources::DataSources, storage::stable_hash};
 
 /// This struct represents the data that we care about extracting from the rss feed
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct NewsArticle{
+    /// Identifies the article across fetches and sessions, the feed's GUID or a hash of the link.
+    /// Articles stored by versions without ids are given the hash of their link when loaded
+    #[serde(default)]
+    pub id: String,
     pub authors: Vec<String>,
     pub title: String,
     pub summary: String,
+    /// The full article body where the feed carries one, as HTML or plain text
+    pub content: Option<String>,
     pub link: String,
     pub source: DataSources,
-}
\ No newline at end of file
+    pub published: Option<DateTime<FixedOffset>>,
+}
+
+impl NewsArticle {
+    /// The id given to articles whose feed doesn't provide one
+    ///
+    /// Arguments
+    /// * link - The link to the article
+    pub(crate) fn id_from_link(link: &str) -> String {
+        format!("{:016x}", stable_hash(link))
+    }
+
+    /// A bare article for tests, its link doubling as its id and title
+    ///
+    /// Arguments
+    /// * source - The source the article belongs to
+    /// * link - The link to the article
+    #[cfg(test)]
+    pub(crate) fn test(source: &DataSources, link: &str) -> NewsArticle {
+        NewsArticle {
+            id: link.to_string(),
+            authors: vec![],
+            title: link.to_string(),
+            summary: "".to_string(),
+            content: None,
+            link: link.to_string(),
+            source: source.clone(),
+            published: None,
+        }
+    }
+
+    /// Builds an article from an RSS item, items without a description are skipped
+    ///
+    /// Arguments
+    /// * item - The RSS item to convert
+    /// * source - The source the item was fetched from
+    pub(crate) fn from_rss_item(item: &rss::Item, source: &DataSources) -> Option<NewsArticle> {
+        let summary = item.description()?.to_string();
+
+        let authors: Vec<String> = match item.author(){
+            Some(auth) => vec![auth.to_string()],
+            None => vec!["".to_string()],
+        };
+
+        let title: String = match item.title() {
+            Some(tit) => tit.to_string(),
+            None => "".to_string(),
+        };
+
+        let link = match item.link() {
+            Some(url) => url.to_string(),
+            None => "".to_string(),
+        };
+
+        let id = match item.guid() {
+            Some(guid) if !guid.value().is_empty() => guid.value().to_string(),
+            _ => NewsArticle::id_from_link(&link),
+        };
+
+        // RSS asks for RFC 2822 dates, but plenty of feeds publish RFC 3339 instead
+        let published = item
+            .pub_date()
+            .and_then(|date| DateTime::parse_from_rfc2822(date).or_else(|_| DateTime::parse_from_rfc3339(date)).ok());
+
+        let content = item.content().map(|content| content.to_string());
+
+        Some(NewsArticle{ id, authors, title, summary, content, link, source: source.clone(), published })
+    }
+
+    /// Builds an article from an Atom entry
+    ///
+    /// Atom entries may carry a summary, full content, or neither (e.g. YouTube channels), so the summary
+    /// falls back to the content and then to an empty string rather than dropping the entry.
+    /// Entries without their own authors inherit the feed level authors.
+    ///
+    /// Arguments
+    /// * entry - The Atom entry to convert
+    /// * feed - The feed the entry belongs to
+    /// * source - The source the feed was fetched from
+    pub(crate) fn from_atom_entry(entry: &atom_syndication::Entry, feed: &atom_syndication::Feed, source: &DataSources) -> NewsArticle {
+        let authors: Vec<String> = match (entry.authors(), feed.authors()) {
+            ([], []) => vec!["".to_string()],
+            ([], feed_authors) => feed_authors.iter().map(|person| person.name().to_string()).collect(),
+            (entry_authors, _) => entry_authors.iter().map(|person| person.name().to_string()).collect(),
+        };
+
+        let title = entry.title().as_str().to_string();
+
+        let summary = match (entry.summary(), entry.content().and_then(|content| content.value())) {
+            (Some(summary), _) => summary.as_str().to_string(),
+            (None, Some(content)) => content.to_string(),
+            (None, None) => "".to_string(),
+        };
+
+        // Prefer the alternate link (the article itself), falling back to whatever link comes first
+        let link = entry
+            .links()
+            .iter()
+            .find(|link| link.rel() == "alternate")
+            .or_else(|| entry.links().first())
+            .map(|link| link.href().to_string())
+            .unwrap_or_default();
+
+        let id = match entry.id() {
+            "" => NewsArticle::id_from_link(&link),
+            id => id.to_string(),
+        };
+
+        // Atom requires an updated date but not a published one, entries missing both read as the epoch
+        let published = entry
+            .published()
+            .copied()
+            .or(Some(*entry.updated()))
+            .filter(|date| date.timestamp() != 0);
+
+        let content = entry.content().and_then(|content| content.value()).map(|content| content.to_string());
+
+        NewsArticle{ id, authors, title, summary, content, link, source: source.clone(), published }
+    }
+}
@@ -1,50 +1,234 @@
 // Code section to fetch RSS data in a way we can understand
+use std::time::Duration;
+use log::{info, warn};
 use rss::Channel;
-use tokio::sync::mpsc::{Sender, Receiver, self};
-use super::{newsarticle::NewsArticle, datasources::DataSources};
-
-/// Collects data from the channel URL
-pub(crate) async fn get_channel(url : &str) -> Result<Channel, ()> {
-    let content =  match reqwest::get(url).await {
-        Ok(get_result) => {
-            match get_result.bytes().await{
-                Ok(get_result_bytes) => get_result_bytes,
-                Err(_) => return Err(()),
-            }
-        },
-        Err(_) => return Err(()),
-    };
+use reqwest::Client;
+use tokio::{sync::mpsc::{Sender, Receiver, UnboundedSender, self}, time::Instant};
+use super::{
+    newsarticle::NewsArticle, datasources::DataSources, jsonfeed::JsonFeed, fetcherror::FetchError,
+    fetchstatus::SourceStatus, newsroomstate::NewsroomTransitions, fetchpolicy::FetchPolicy,
+    httpcache::{HttpCache, CachedResponse},
+};
+
+/// A parsed feed, in whichever format the source publishes
+#[derive(Debug)]
+pub(crate) enum Feed {
+    Rss(Box<Channel>),
+    Atom(Box<atom_syndication::Feed>),
+    Json(Box<JsonFeed>),
+}
 
-    match Channel::read_from(&content[..]) {
-        Ok(channel) => Ok(channel),
-        Err(_) => Err(()),
+impl Feed {
+    /// Parses a feed document, detecting whether it is RSS 2.0, Atom 1.0 or JSON Feed
+    ///
+    /// Arguments
+    /// * content - The raw bytes of the feed document
+    /// * content_type - The Content-Type header the document was served with, if any
+    pub(crate) fn parse(content: &[u8], content_type: Option<&str>) -> Result<Feed, FetchError> {
+        if Feed::is_json(content, content_type) {
+            return JsonFeed::read_from(content).map(|feed| Feed::Json(Box::new(feed)));
+        }
+        let rss_error = match Channel::read_from(content) {
+            Ok(channel) => return Ok(Feed::Rss(Box::new(channel))),
+            Err(e) => e,
+        };
+        match atom_syndication::Feed::read_from(content) {
+            Ok(feed) => Ok(Feed::Atom(Box::new(feed))),
+            // Neither parser recognised the root element, so this isn't a feed at all
+            Err(atom_syndication::Error::InvalidStartTag) => match rss_error {
+                rss::Error::InvalidStartTag => Err(FetchError::NotAFeed),
+                rss_error => Err(FetchError::Parse(rss_error.to_string())),
+            },
+            Err(atom_error) => Err(FetchError::Parse(atom_error.to_string())),
+        }
+    }
+
+    /// Whether a document should be read as JSON, either because it was served as JSON
+    /// or because it looks like a JSON object (plenty of servers send JSON Feeds as text/plain)
+    fn is_json(content: &[u8], content_type: Option<&str>) -> bool {
+        let served_as_json = content_type
+            .map(|mime| mime.contains("json"))
+            .unwrap_or(false);
+        let looks_like_json = content
+            .iter()
+            .find(|byte| !byte.is_ascii_whitespace())
+            .map(|byte| *byte == b'{')
+            .unwrap_or(false);
+        served_as_json || looks_like_json
+    }
+
+    /// The feed's own name for itself, if it has one
+    pub(crate) fn title(&self) -> Option<String> {
+        let title = match self {
+            Feed::Rss(channel) => channel.title().to_string(),
+            Feed::Atom(feed) => feed.title().value.clone(),
+            Feed::Json(feed) => feed.title.clone().unwrap_or_default(),
+        };
+        Some(title.trim().to_string()).filter(|title| !title.is_empty())
+    }
+
+    /// Converts every item in the feed into a news article
+    ///
+    /// Arguments
+    /// * source - The source the feed was fetched from
+    pub(crate) fn articles(&self, source: &DataSources) -> Vec<NewsArticle> {
+        match self {
+            Feed::Rss(channel) => channel
+                .items()
+                .iter()
+                .filter_map(|item| NewsArticle::from_rss_item(item, source))
+                .collect(),
+            Feed::Atom(feed) => feed
+                .entries()
+                .iter()
+                .map(|entry| NewsArticle::from_atom_entry(entry, feed, source))
+                .collect(),
+            Feed::Json(feed) => feed.articles(source),
+        }
     }
 }
 
-/// Fetches articles from a series of sources, returns a list of articles. Articles are fetched async
-pub(crate) async fn fetch_articles(sources: Vec<DataSources>) -> Vec<NewsArticle>{
-    let mut rx: Receiver<NewsArticle>;
-
-    // Asynchronously fetches news articles from the rss feeds
-    // Define a local scope so that we can drop TX, meaning the channel will close when all threads resolve
-    {
-        let (tx, rx_local): (Sender<NewsArticle>, Receiver<NewsArticle>) = mpsc::channel(100);
-        rx = rx_local;
-        for source in sources{
-            tokio::spawn(source.stream_articles(tx.clone()));
+/// Collects data from the channel URL, making a single attempt
+/// If we have a cached copy of the feed the request is made conditional, and a 304 Not Modified
+/// response reuses the cached copy rather than downloading the feed again. A cached copy whose body
+/// is missing or empty counts as a miss, so the whole feed is asked for
+///
+/// Arguments
+/// * client - The http client to fetch with
+/// * url - The url of the feed
+/// * timeout - How long the request may take in total
+/// * cache - Where the last response from each feed is remembered
+pub(crate) async fn get_channel(client: &Client, url : &str, timeout: Duration, cache: &HttpCache) -> Result<Feed, FetchError> {
+    let cached = cache.load(url).filter(|(_, body)| !body.is_empty());
+    let mut request = client.get(url).timeout(timeout);
+    if let Some((validators, _)) = &cached {
+        if let Some(etag) = &validators.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
         }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let get_result = request.send().await?;
+    match (get_result.status(), &cached) {
+        (reqwest::StatusCode::NOT_MODIFIED, Some((validators, body))) => {
+            info!("{} not modified, reusing cached feed", url);
+            return Feed::parse(body, validators.content_type.as_deref());
+        },
+        // We didn't ask for a conditional response, so there is no feed in an empty body
+        (reqwest::StatusCode::NOT_MODIFIED, None) => return Err(FetchError::Http(304)),
+        _ => {},
     }
 
-    let mut fetched_articles: Vec<NewsArticle> = vec![];
+    let get_result = get_result.error_for_status()?;
+    let header = |name: reqwest::header::HeaderName| get_result
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+    let validators = CachedResponse {
+        etag: header(reqwest::header::ETAG),
+        last_modified: header(reqwest::header::LAST_MODIFIED),
+        content_type: header(reqwest::header::CONTENT_TYPE),
+    };
+    let content = get_result.bytes().await?;
+
+    let feed = Feed::parse(&content[..], validators.content_type.as_deref())?;
+    // Only feeds which parse are worth keeping, and only servers which send validators can answer a conditional request
+    if validators.etag.is_some() || validators.last_modified.is_some() {
+        cache.store(url, &validators, &content);
+    }
+    Ok(feed)
+}
 
+/// Collects data from the channel URL, retrying with exponential backoff when the policy allows
+///
+/// Arguments
+/// * client - The http client to fetch with
+/// * url - The url of the feed
+/// * policy - The timeout and retry settings to use
+/// * cache - Where the last response from each feed is remembered
+pub(crate) async fn get_channel_with_retries(client: &Client, url: &str, policy: &FetchPolicy, cache: &HttpCache) -> Result<Feed, FetchError> {
+    let mut retry = 0;
     loop {
-        match rx.recv().await {
-            Some(news_article_message) => {
-                fetched_articles.push(news_article_message)
+        match get_channel(client, url, policy.timeout(), cache).await {
+            Err(e) if e.is_retryable() && retry < policy.retries => {
+                info!("Fetching {} failed ({}), retrying", url, e);
+                tokio::time::sleep(policy.backoff(retry)).await;
+                retry += 1;
             },
-            None => break,
+            result => return result,
         }
     }
+}
+
+/// Fetches a single source, relaying its articles and how it went over the progress channel once it finishes.
+/// Returns the number of articles fetched
+///
+/// Arguments
+/// * source - The source to fetch
+/// * client - The http client to fetch with
+/// * policy - The global fetch settings
+/// * cache - Where the last response from each feed is remembered
+/// * deadline - When the whole fetch must be finished by, the source is abandoned after this
+/// * progress - A channel to send the source's articles and final status over
+async fn fetch_source(source: DataSources, client: Client, policy: FetchPolicy, cache: HttpCache, deadline: Instant, progress: UnboundedSender<NewsroomTransitions>) -> usize {
+    let started = Instant::now();
+    let (tx, mut rx): (Sender<NewsArticle>, Receiver<NewsArticle>) = mpsc::channel(100);
+
+    // Gather the articles as the source streams them, the channel closes once stream_articles drops tx
+    let gather = async {
+        let mut articles: Vec<NewsArticle> = vec![];
+        while let Some(article) = rx.recv().await {
+            articles.push(article);
+        }
+        articles
+    };
+    let fetch = async { tokio::join!(source.clone().stream_articles(client, policy, cache, tx), gather) };
+    let (result, articles) = match tokio::time::timeout_at(deadline, fetch).await {
+        Ok(fetched) => fetched,
+        Err(_) => (Err(FetchError::Timeout), vec![]),
+    };
+
+    let status = match result {
+        Ok(count) => SourceStatus::Loaded { articles: count, elapsed: started.elapsed() },
+        Err(error) => {
+            warn!("Failed to fetch {} ({}): {}", source.name, source.url, error);
+            SourceStatus::Failed { error, elapsed: started.elapsed() }
+        },
+    };
+    let succeeded = matches!(status, SourceStatus::Loaded { .. });
+    let count = articles.len();
+
+    let _ = progress.send(NewsroomTransitions::SourceStatus(source.clone(), status));
+    // A failed source keeps whatever articles it provided last time
+    if succeeded {
+        let _ = progress.send(NewsroomTransitions::ReturnSourceMedia(source, articles));
+    }
+    count
+}
+
+/// Fetches articles from a series of sources async, relaying each source's articles over the progress
+/// channel as soon as that source finishes. Returns the total number of articles fetched
+///
+/// Arguments
+/// * sources - The sources to fetch
+/// * policy - The timeout, retry and deadline settings to use
+/// * cache - Where the last response from each feed is remembered
+/// * progress - A channel which each source's articles and status are reported over
+pub(crate) async fn fetch_articles(sources: Vec<DataSources>, policy: FetchPolicy, cache: HttpCache, progress: UnboundedSender<NewsroomTransitions>) -> usize{
+    let client = Client::new();
+    let deadline = Instant::now() + policy.deadline();
+    let handles: Vec<_> = sources
+        .into_iter()
+        .map(|source| tokio::spawn(fetch_source(source, client.clone(), policy.clone(), cache.clone(), deadline, progress.clone())))
+        .collect();
+
+    let mut fetched_articles = 0;
+    for handle in handles {
+        fetched_articles += handle.await.unwrap_or(0);
+    }
     fetched_articles
 }
 
@@ -52,11 +236,14 @@ pub(crate) async fn fetch_articles(sources: Vec<DataSources>) -> Vec<NewsArticle
 mod test {
     
     use super::*;
+    use crate::newsroomcore::{storage::TempPath, stubserver::{StubServer, StubResponse}};
 
     // Test that we're able to correctly read from the CBC rss channel
     #[tokio::test]
     async fn test_rss_fetch(){
-        let ch = get_channel("https://www.cbc.ca/cmlink/rss-topstories").await.unwrap();
+        let Feed::Rss(ch) = get_channel(&Client::new(), "https://www.cbc.ca/cmlink/rss-topstories", FetchPolicy::default().timeout(), &HttpCache::disabled()).await.unwrap() else {
+            panic!("CBC should publish an RSS channel")
+        };
         let items = ch.items();
         println!("{}", items.len());
         let _entry = &items[0];
@@ -65,15 +252,291 @@ mod test {
         assert_eq!(ch.title(), "CBC | Top Stories News");
     }
 
+    fn fixture_source() -> DataSources {
+        DataSources::new("fixture", "https://example.com/feed")
+    }
+
+    // Test that RSS channels are detected and items without a description are skipped
+    #[test]
+    fn test_parse_rss(){
+        let feed = Feed::parse(include_bytes!("../../resources/fixtures/rss.xml"), Some("application/rss+xml")).unwrap();
+        assert!(matches!(feed, Feed::Rss(_)));
+        assert_eq!(feed.title().as_deref(), Some("Newsroom Test Channel"));
+
+        let articles = feed.articles(&fixture_source());
+        assert_eq!(articles.len(), 2);
+        assert_eq!(articles[0].title, "First headline");
+        assert_eq!(articles[0].link, "https://example.com/first");
+        assert_eq!(articles[0].source.name, "fixture");
+
+        // The full body comes from content:encoded when the channel provides it
+        assert_eq!(articles[0].content.as_deref(), Some("<p>The first story, <b>in full</b></p>"));
+        assert_eq!(articles[1].content, None);
+
+        // Dates are read from pubDate, and items without one are left undated
+        assert_eq!(articles[0].published, chrono::DateTime::parse_from_rfc2822("Tue, 03 Oct 2023 14:30:00 GMT").ok());
+
+        // GUIDs identify articles, with the link hash filling in for items without one
+        assert_eq!(articles[1].id, "example-second");
+        let feed = Feed::parse(b"<rss version=\"2.0\"><channel><title>t</title><link>l</link><description>d</description><item><link>https://example.com/a</link><description>a</description></item></channel></rss>", None).unwrap();
+        assert_eq!(feed.articles(&fixture_source())[0].id, NewsArticle::id_from_link("https://example.com/a"));
+    }
+
+    // Test that Atom feeds are detected and entries are mapped onto articles
+    #[test]
+    fn test_parse_atom(){
+        let feed = Feed::parse(include_bytes!("../../resources/fixtures/atom.xml"), None).unwrap();
+        assert!(matches!(feed, Feed::Atom(_)));
+        assert_eq!(feed.title().as_deref(), Some("Release notes from newsroom"));
+
+        let articles = feed.articles(&fixture_source());
+        assert_eq!(articles.len(), 3);
+
+        // Summary is preferred over content, and the alternate link over any other
+        assert_eq!(articles[0].title, "v0.2.0");
+        assert_eq!(articles[0].summary, "Adds Atom feeds");
+        assert_eq!(articles[0].link, "https://example.com/releases/v0.2.0");
+        assert_eq!(articles[0].authors, vec!["Spencer", "Jack"]);
+
+        // Content fills in for a missing summary, and feed authors for missing entry authors
+        assert_eq!(articles[1].summary, "<p>First release</p>");
+        assert_eq!(articles[1].link, "https://example.com/releases/v0.1.0");
+        assert_eq!(articles[1].authors, vec!["Feed Maintainer"]);
+
+        // Entries with neither summary nor content are kept
+        assert_eq!(articles[2].summary, "");
+
+        // The published date is preferred, falling back to the updated date
+        assert_eq!(articles[0].published, chrono::DateTime::parse_from_rfc3339("2023-10-03T14:00:00Z").ok());
+        assert_eq!(articles[1].published, chrono::DateTime::parse_from_rfc3339("2023-09-01T08:00:00Z").ok());
+    }
+
+    // Test that JSON Feeds are detected from either the content type or the document itself
+    #[test]
+    fn test_parse_json_feed(){
+        let content = include_bytes!("../../resources/fixtures/feed.json");
+        let feed = Feed::parse(content, Some("application/feed+json")).unwrap();
+        assert!(matches!(feed, Feed::Json(_)));
+        assert_eq!(feed.title().as_deref(), Some("Newsroom Test Feed"));
+        assert_eq!(feed.articles(&fixture_source()).len(), 3);
+
+        let feed = Feed::parse(content, Some("text/plain; charset=utf-8")).unwrap();
+        assert!(matches!(feed, Feed::Json(_)));
+    }
+
+    // Test that documents which aren't feeds are told apart from broken feeds
+    #[test]
+    fn test_parse_errors(){
+        let result = Feed::parse(b"<html><body>Not a feed</body></html>", Some("text/html"));
+        assert_eq!(result.unwrap_err(), FetchError::NotAFeed);
+
+        let result = Feed::parse(b"<rss version=\"2.0\"><channel><title>Cut off", None);
+        assert!(matches!(result.unwrap_err(), FetchError::Parse(_)));
+
+        let result = Feed::parse(b"{\"version\": \"https://jsonfeed.org/version/1.1\", \"items\": [", None);
+        assert!(matches!(result.unwrap_err(), FetchError::Parse(_)));
+    }
+
+    // Test that sources which can't be reached are reported rather than silently dropped
+    #[tokio::test]
+    async fn test_fetch_failures(){
+        let unreachable = DataSources::new("unreachable", "http://127.0.0.1:1/feed");
+        let (progress, mut progress_rx) = mpsc::unbounded_channel();
+        let articles = fetch_articles(vec![unreachable], FetchPolicy { retries: 0, ..FetchPolicy::default() }, HttpCache::disabled(), progress).await;
+        assert_eq!(articles, 0);
+
+        let Some(NewsroomTransitions::SourceStatus(source, status)) = progress_rx.recv().await else {
+            panic!("Expected a source status update")
+        };
+        assert_eq!(source.name, "unreachable");
+        assert!(matches!(status, SourceStatus::Failed { error: FetchError::Connect(_), .. }));
+    }
+
+    fn quick_policy() -> FetchPolicy {
+        FetchPolicy { timeout_secs: 1, retries: 2, backoff_ms: 10, deadline_secs: 5 }
+    }
+
+    const RSS_FIXTURE: &[u8] = include_bytes!("../../resources/fixtures/rss.xml");
+
+    // Test that a server which never answers is abandoned after the request timeout
+    #[tokio::test]
+    async fn test_request_timeout(){
+        let server = StubServer::start(vec![
+            ("/feed", vec![StubResponse::ok(RSS_FIXTURE).delay(Duration::from_secs(5))]),
+        ]).await;
+        let policy = FetchPolicy { retries: 0, ..quick_policy() };
+
+        let result = get_channel_with_retries(&Client::new(), &server.url("/feed"), &policy, &HttpCache::disabled()).await;
+        assert_eq!(result.unwrap_err(), FetchError::Timeout);
+    }
+
+    // Test that server errors are retried until the feed comes through
+    #[tokio::test]
+    async fn test_retry_server_errors(){
+        let server = StubServer::start(vec![
+            ("/feed", vec![StubResponse::status(503), StubResponse::status(502), StubResponse::ok(RSS_FIXTURE)]),
+        ]).await;
+
+        let result = get_channel_with_retries(&Client::new(), &server.url("/feed"), &quick_policy(), &HttpCache::disabled()).await;
+        assert!(matches!(result, Ok(Feed::Rss(_))));
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    // Test that we give up once the retries run out, and don't retry errors that won't go away
+    #[tokio::test]
+    async fn test_retry_limits(){
+        let server = StubServer::start(vec![
+            ("/down", vec![StubResponse::status(503)]),
+            ("/missing", vec![StubResponse::status(404)]),
+        ]).await;
+        let client = Client::new();
+
+        let result = get_channel_with_retries(&client, &server.url("/down"), &quick_policy(), &HttpCache::disabled()).await;
+        assert_eq!(result.unwrap_err(), FetchError::Http(503));
+        let result = get_channel_with_retries(&client, &server.url("/missing"), &quick_policy(), &HttpCache::disabled()).await;
+        assert_eq!(result.unwrap_err(), FetchError::Http(404));
+
+        let requests = server.requests();
+        assert_eq!(requests.iter().filter(|request| request.path == "/down").count(), 3);
+        assert_eq!(requests.iter().filter(|request| request.path == "/missing").count(), 1);
+    }
+
+    // Test that the served content type is used to detect JSON Feeds
+    #[tokio::test]
+    async fn test_fetch_json_feed(){
+        let server = StubServer::start(vec![
+            ("/feed.json", vec![StubResponse::ok(&include_bytes!("../../resources/fixtures/feed.json")[..]).header("Content-Type", "application/feed+json")]),
+        ]).await;
+
+        let result = get_channel(&Client::new(), &server.url("/feed.json"), Duration::from_secs(1), &HttpCache::disabled()).await;
+        assert!(matches!(result, Ok(Feed::Json(_))));
+    }
+
+    // A cache in a fresh directory, so tests don't share or leave behind cached feeds. The directory goes with the TempPath
+    fn temp_cache(name: &str) -> (TempPath, HttpCache) {
+        let dir = TempPath::new(name);
+        let cache = HttpCache::new(dir.to_path_buf());
+        (dir, cache)
+    }
+
+    // Test that a cached ETag is sent back and a 304 reuses the cached feed
+    #[tokio::test]
+    async fn test_conditional_etag(){
+        let server = StubServer::start(vec![
+            ("/feed", vec![
+                StubResponse::ok(RSS_FIXTURE).header("ETag", "\"v1\"").header("Content-Type", "application/rss+xml"),
+                StubResponse::status(304),
+            ]),
+        ]).await;
+        let (_dir, cache) = temp_cache("etag");
+        let client = Client::new();
+
+        let first = get_channel(&client, &server.url("/feed"), Duration::from_secs(1), &cache).await.unwrap();
+        let second = get_channel(&client, &server.url("/feed"), Duration::from_secs(1), &cache).await.unwrap();
+        assert_eq!(first.articles(&fixture_source()).len(), second.articles(&fixture_source()).len());
+
+        let requests = server.requests();
+        assert_eq!(requests[0].headers.get("if-none-match"), None);
+        assert_eq!(requests[1].headers.get("if-none-match").map(String::as_str), Some("\"v1\""));
+    }
+
+    // Test that validators cached without a usable body aren't sent, so the whole feed comes back,
+    // and that a 304 nobody asked for isn't read as an empty feed
+    #[tokio::test]
+    async fn test_conditional_without_body(){
+        let server = StubServer::start(vec![
+            ("/feed", vec![StubResponse::ok(RSS_FIXTURE).header("ETag", "\"v2\"")]),
+            ("/stray", vec![StubResponse::status(304)]),
+        ]).await;
+        let (_dir, cache) = temp_cache("emptybody");
+        let validators = CachedResponse { etag: Some("\"v1\"".to_string()), last_modified: None, content_type: None };
+        cache.store(&server.url("/feed"), &validators, b"");
+        let client = Client::new();
+
+        let feed = get_channel(&client, &server.url("/feed"), Duration::from_secs(1), &cache).await.unwrap();
+        assert!(!feed.articles(&fixture_source()).is_empty());
+        assert_eq!(server.requests()[0].headers.get("if-none-match"), None);
+        assert_eq!(cache.load(&server.url("/feed")).map(|(validators, _)| validators.etag), Some(Some("\"v2\"".to_string())));
+
+        let stray = get_channel(&client, &server.url("/stray"), Duration::from_secs(1), &cache).await;
+        assert!(matches!(stray, Err(FetchError::Http(304))));
+    }
+
+    // Test that Last-Modified is used when the server sends no ETag, and that uncacheable responses aren't stored
+    #[tokio::test]
+    async fn test_conditional_last_modified(){
+        let modified = "Tue, 03 Oct 2023 18:30:00 GMT";
+        let server = StubServer::start(vec![
+            ("/dated", vec![StubResponse::ok(RSS_FIXTURE).header("Last-Modified", modified), StubResponse::status(304)]),
+            ("/plain", vec![StubResponse::ok(RSS_FIXTURE)]),
+        ]).await;
+        let (_dir, cache) = temp_cache("modified");
+        let client = Client::new();
+
+        for _ in 0..2 {
+            get_channel(&client, &server.url("/dated"), Duration::from_secs(1), &cache).await.unwrap();
+            get_channel(&client, &server.url("/plain"), Duration::from_secs(1), &cache).await.unwrap();
+        }
+
+        let requests = server.requests();
+        let dated: Vec<_> = requests.iter().filter(|r| r.path == "/dated").collect();
+        assert_eq!(dated[1].headers.get("if-modified-since").map(String::as_str), Some(modified));
+        assert!(requests.iter().filter(|r| r.path == "/plain").all(|r| !r.headers.contains_key("if-modified-since")));
+    }
+
+    // Test that a per source timeout override is respected over the global timeout
+    #[tokio::test]
+    async fn test_source_timeout_override(){
+        let server = StubServer::start(vec![
+            ("/feed", vec![StubResponse::ok(RSS_FIXTURE).delay(Duration::from_millis(1500))]),
+        ]).await;
+        let mut source = DataSources::new("slow", &server.url("/feed"));
+        source.timeout_secs = Some(3);
+        let (tx, _rx) = mpsc::channel(100);
+
+        let result = source.stream_articles(Client::new(), FetchPolicy { retries: 0, ..quick_policy() }, HttpCache::disabled(), tx).await;
+        assert_eq!(result.unwrap(), 2);
+    }
+
+    // Test that sources still going at the deadline are reported as timed out while the rest come through
+    #[tokio::test]
+    async fn test_fetch_deadline(){
+        let server = StubServer::start(vec![
+            ("/fast", vec![StubResponse::ok(RSS_FIXTURE)]),
+            ("/slow", vec![StubResponse::status(503)]),
+        ]).await;
+        let fast = DataSources::new("fast", &server.url("/fast"));
+        let slow = DataSources::new("slow", &server.url("/slow"));
+        // The slow source keeps retrying with long backoffs, so only the deadline can stop it
+        let policy = FetchPolicy { timeout_secs: 1, retries: 10, backoff_ms: 1000, deadline_secs: 1 };
+        let (progress, mut progress_rx) = mpsc::unbounded_channel();
+
+        let started = Instant::now();
+        let articles = fetch_articles(vec![fast, slow], policy, HttpCache::disabled(), progress).await;
+        assert_eq!(articles, 2);
+        assert!(started.elapsed() < Duration::from_secs(3));
+
+        progress_rx.close();
+        let mut statuses = vec![];
+        while let Some(transition) = progress_rx.recv().await {
+            if let NewsroomTransitions::SourceStatus(source, status) = transition {
+                statuses.push((source.name, status));
+            }
+        }
+        assert!(statuses.iter().any(|(name, status)| name == "fast" && matches!(status, SourceStatus::Loaded { articles: 2, .. })));
+        assert!(statuses.iter().any(|(name, status)| name == "slow" && matches!(status, SourceStatus::Failed { error: FetchError::Timeout, .. })));
+    }
+
     #[tokio::test]
     async fn test_fetch_articles(){
-        let cbc = DataSources{name: "cbc".to_string(), url: "https://www.cbc.ca/cmlink/rss-topstories".to_string()};
-        let cnn = DataSources{name: "cnn".to_string(), url: "http://rss.cnn.com/rss/cnn_topstories.rss".to_string()};
-        let globe: DataSources = DataSources { name: "globe and mail".to_string(), url: "https://www.theglobeandmail.com/arc/outboundfeeds/rss/category/canada/".to_string()};
+        let cbc = DataSources::new("cbc", "https://www.cbc.ca/cmlink/rss-topstories");
+        let cnn = DataSources::new("cnn", "http://rss.cnn.com/rss/cnn_topstories.rss");
+        let globe: DataSources = DataSources::new("globe and mail", "https://www.theglobeandmail.com/arc/outboundfeeds/rss/category/canada/");
         let sources = vec![cbc, cnn, globe];
 
         // Fetch articles and add them to the app
-        let fetched_articles = fetch_articles(sources).await;
-        assert!(fetched_articles.len()>1);
+        let (progress, _progress_rx) = mpsc::unbounded_channel();
+        let fetched_articles = fetch_articles(sources, FetchPolicy::default(), HttpCache::disabled(), progress).await;
+        assert!(fetched_articles>1);
     }
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
-use super::{newsarticle::NewsArticle, datasources::DataSources};
+use std::path::PathBuf;
+use super::{formaction::FormAction, sortmode::SortMode, newsarticle::NewsArticle, datasources::DataSources, discovery::Discovery, fetcherror::FetchError, fetchstatus::SourceStatus};
 
 /// Enum to represent our own app state
 #[derive(Debug, Clone)]
@@ -13,9 +14,89 @@ pub enum NewsroomState{
 pub enum NewsroomTransitions{
     Loaded,
     ToSettings,
+    ToSaved,
     ExitSettings,
     FetchMedia(Vec<DataSources>),
     ReturnMedia(Vec<NewsArticle>),
+    /// The latest articles from a single source, replacing any it previously provided
+    ReturnSourceMedia(DataSources, Vec<NewsArticle>),
+    SourceStatus(DataSources, SourceStatus),
+    /// Flip the read state of the selected article
+    ToggleRead,
+    /// Mark the article with the given id as read
+    MarkRead(String),
+    /// Star the selected article, or unstar it if it is already starred
+    ToggleSaved,
+    /// Switch the news list to the next sort mode
+    CycleSort,
+    /// Switch the news list to the given sort mode
+    SetSort(SortMode),
+    /// Narrow the news list to the source with the given url, or show every source's articles
+    FilterSource(Option<String>),
+    /// Read the article at the given position in the list being shown, counting from 0
+    OpenArticle(usize),
+    /// Write the articles in the news list to a markdown file
+    ExportDigest(PathBuf),
+    /// Show or hide the source sidebar
+    ToggleSidebar,
+    /// Open the search line with an empty query
+    StartSearch,
+    SearchInput(char),
+    SearchBackspace,
+    /// Close the search line, keeping the list filtered
+    ConfirmSearch,
+    /// Close the search line and show every article again
+    CancelSearch,
+    /// Select the next article matching the search
+    NextMatch,
+    /// Select the previous article matching the search
+    PreviousMatch,
+    /// Read the selected article inside the terminal
+    OpenReader,
+    CloseReader,
+    /// Scroll the reader by a number of rows, negative to scroll up
+    ScrollReader(i32),
+    /// Show the list of keys, or hide it if it is showing
+    ToggleHelp,
+    /// Scroll the list of keys by a number of rows, negative to scroll up
+    ScrollHelp(i32),
+    /// The full story fetched from the page of the article with the given id
+    ReturnFullText(String, Result<String, FetchError>),
+    /// Open a form in the settings tab
+    OpenForm(FormAction),
+    FormInput(char),
+    FormBackspace,
+    /// Replace the text of the focused field
+    FormSet(String),
+    NextField,
+    PreviousField,
+    SubmitForm,
+    CancelForm,
+    /// What was found at the url of a source being added or edited, along with the index of the source being edited
+    ReturnFeedCheck(DataSources, Option<usize>, Result<Discovery, FetchError>),
+    /// Enable the selected source if it is disabled, or disable it
+    ToggleSource,
+    /// Swap the selected source with the one above it
+    MoveSourceUp,
+    /// Swap the selected source with the one below it
+    MoveSourceDown,
+    /// Pick a theme from the settings tab, previewing each one as it is highlighted
+    OpenThemePicker,
+    /// Keep the highlighted theme and save it
+    ConfirmTheme,
+    /// Go back to the theme in use before picking
+    CancelThemePicker,
+    /// Switch to the theme with the given name and save it
+    SetTheme(String),
+    /// Open the command line with nothing typed
+    OpenCommand,
+    CommandInput(char),
+    CommandBackspace,
+    /// Complete the word being typed into the command line
+    CompleteCommand,
+    /// Carry out the command typed, keeping the command line open with the reason if it can't be
+    SubmitCommand,
+    CancelCommand,
     Up,
     Down,
     Left,
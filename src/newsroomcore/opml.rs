@@ -0,0 +1,180 @@
+// Code section to read and write OPML, the format feed readers use to move subscriptions between each other
+use std::error::Error;
+use quick_xml::{encoding::Decoder, escape::escape, events::{BytesStart, Event}, Reader};
+use super::datasources::DataSources;
+
+/// Reads the feeds listed in an OPML document. Outlines nested inside other outlines
+/// are filed under those outlines as categories, joined with '/'
+///
+/// Arguments
+/// * content - The OPML document
+pub fn read_opml(content: &str) -> Result<Vec<DataSources>, Box<dyn Error>> {
+    let mut reader = Reader::from_str(content);
+    reader.config_mut().trim_text(true);
+
+    let mut sources = vec![];
+    let mut is_opml = false;
+    // The folders we are inside, None for feeds which (unusually) have outlines nested inside them
+    let mut folders: Vec<Option<String>> = vec![];
+    loop {
+        match reader.read_event()? {
+            Event::Start(tag) | Event::Empty(tag) if tag.name().as_ref() == b"opml" => is_opml = true,
+            Event::Start(tag) if tag.name().as_ref() == b"outline" => {
+                let outline = Outline::read(&tag, reader.decoder())?;
+                match outline.xml_url {
+                    Some(_) => {
+                        sources.push(outline.into_source(&folders));
+                        folders.push(None);
+                    },
+                    None => folders.push(Some(outline.text)),
+                }
+            },
+            Event::Empty(tag) if tag.name().as_ref() == b"outline" => {
+                let outline = Outline::read(&tag, reader.decoder())?;
+                if outline.xml_url.is_some() {
+                    sources.push(outline.into_source(&folders));
+                }
+            },
+            Event::End(tag) if tag.name().as_ref() == b"outline" => {
+                folders.pop();
+            },
+            Event::Eof => break,
+            _ => {},
+        }
+    }
+
+    match is_opml {
+        true => Ok(sources),
+        false => Err("not an OPML document".into()),
+    }
+}
+
+/// The attributes of an outline element that we care about
+struct Outline {
+    text: String,
+    xml_url: Option<String>,
+    /// OPML 2.0's own category attribute, a comma separated list of '/' separated paths
+    category: Option<String>,
+}
+
+impl Outline {
+    fn read(tag: &BytesStart, decoder: Decoder) -> Result<Outline, Box<dyn Error>> {
+        let mut text = None;
+        let mut title = None;
+        let mut xml_url = None;
+        let mut category = None;
+        for attribute in tag.attributes() {
+            let attribute = attribute?;
+            let value = attribute.decode_and_unescape_value(decoder)?.trim().to_string();
+            // Attribute names are meant to be camel case, but plenty of exporters lowercase them
+            match attribute.key.as_ref().to_ascii_lowercase().as_slice() {
+                b"text" => text = Some(value),
+                b"title" => title = Some(value),
+                b"xmlurl" => xml_url = Some(value),
+                b"category" => category = Some(value),
+                _ => {},
+            }
+        }
+        let xml_url = xml_url.filter(|url| !url.is_empty());
+        let text = text
+            .filter(|text| !text.is_empty())
+            .or(title.filter(|title| !title.is_empty()))
+            .or(xml_url.clone())
+            .unwrap_or_default();
+        Ok(Outline { text, xml_url, category })
+    }
+
+    /// Turns a feed outline into a source, filed under the folders it was found in
+    fn into_source(self, folders: &[Option<String>]) -> DataSources {
+        let mut source = DataSources::new(&self.text, &self.xml_url.unwrap_or_default());
+        let path = folders.iter().flatten().cloned().collect::<Vec<_>>().join("/");
+        source.category = match path.is_empty() {
+            false => Some(path),
+            true => self.category
+                .as_deref()
+                .and_then(|categories| categories.split(',').next())
+                .map(|category| category.trim().trim_matches('/').to_string())
+                .filter(|category| !category.is_empty()),
+        };
+        source
+    }
+}
+
+/// Writes sources out as an OPML 2.0 document, with each category as a folder outline
+///
+/// Arguments
+/// * sources - The sources to write
+pub fn write_opml(sources: &[DataSources]) -> String {
+    let mut opml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n  <head>\n    <title>Newsroom sources</title>\n  </head>\n  <body>\n");
+    let sources: Vec<(Vec<&str>, &DataSources)> = sources
+        .iter()
+        .map(|source| {
+            let folders = source.category.as_deref().map(|category| category.split('/').filter(|folder| !folder.is_empty()).collect()).unwrap_or_default();
+            (folders, source)
+        })
+        .collect();
+    write_outlines(&mut opml, &sources, 0);
+    opml.push_str("  </body>\n</opml>\n");
+    opml
+}
+
+/// Writes the outlines for sources at one level of folders, in the order each feed or folder first appears
+fn write_outlines(opml: &mut String, sources: &[(Vec<&str>, &DataSources)], depth: usize) {
+    let indent = "  ".repeat(depth + 2);
+    let mut written_folders: Vec<&str> = vec![];
+    for (folders, source) in sources {
+        match folders.get(depth) {
+            None => opml.push_str(&format!(
+                "{}<outline type=\"rss\" text=\"{}\" title=\"{}\" xmlUrl=\"{}\"/>\n",
+                indent, escape(source.name.as_str()), escape(source.name.as_str()), escape(source.url.as_str())
+            )),
+            Some(folder) if !written_folders.contains(folder) => {
+                written_folders.push(folder);
+                let inside: Vec<(Vec<&str>, &DataSources)> = sources
+                    .iter()
+                    .filter(|(other, _)| other.get(depth) == Some(folder))
+                    .cloned()
+                    .collect();
+                opml.push_str(&format!("{}<outline text=\"{}\" title=\"{}\">\n", indent, escape(*folder), escape(*folder)));
+                write_outlines(opml, &inside, depth + 1);
+                opml.push_str(&format!("{}</outline>\n", indent));
+            },
+            Some(_) => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read_opml(){
+        let sources = read_opml(include_str!("../../resources/fixtures/subscriptions.opml")).unwrap();
+        let summary: Vec<(&str, &str, Option<&str>)> = sources
+            .iter()
+            .map(|source| (source.name.as_str(), source.url.as_str(), source.category.as_deref()))
+            .collect();
+        assert_eq!(summary, vec![
+            ("World News", "https://example.com/world.rss", None),
+            ("Kernel & Friends", "https://example.com/kernel.xml", Some("Tech/Linux")),
+            ("Gadgets", "https://example.com/gadgets.atom", Some("Tech")),
+            ("https://example.com/untitled.rss", "https://example.com/untitled.rss", Some("Tech")),
+            ("Local", "https://example.com/local.json", Some("City/Hall")),
+        ]);
+
+        assert!(read_opml("<rss version=\"2.0\"><channel/></rss>").is_err());
+    }
+
+    #[test]
+    fn test_opml_roundtrip(){
+        let mut linux = DataSources::new("Kernel & \"Friends\"", "https://example.com/kernel.xml?a=1&b=2");
+        linux.category = Some("Tech/Linux".to_string());
+        let mut gadgets = DataSources::new("Gadgets", "https://example.com/gadgets.atom");
+        gadgets.category = Some("Tech".to_string());
+        let sources = vec![DataSources::new("World", "https://example.com/world.rss"), linux, gadgets];
+
+        let opml = write_opml(&sources);
+        assert_eq!(read_opml(&opml).unwrap(), sources);
+    }
+}
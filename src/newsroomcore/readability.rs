@@ -0,0 +1,323 @@
+// Code section to pull the story out of a full web page, in the spirit of Mozilla's Readability
+// Pages are parsed into a rough tree, paragraphs vote for the containers holding them, and the best
+// scoring container (plus any siblings which look like part of the same story) is kept
+use super::html::{tokenize, HtmlToken};
+
+/// Elements which never hold the story, dropped along with everything inside them
+const STRIPPED: [&str; 13] = ["script", "style", "noscript", "template", "nav", "header", "footer", "aside", "form", "button", "iframe", "svg", "select"];
+/// Elements which can't have children
+const VOID: [&str; 12] = ["br", "img", "hr", "meta", "link", "input", "source", "wbr", "area", "col", "embed", "track"];
+/// Class or id fragments marking page furniture rather than the story
+const UNLIKELY: [&str; 17] = ["nav", "menu", "footer", "sidebar", "comment", "share", "social", "related", "promo", "advert", "banner", "sponsor", "newsletter", "subscribe", "popup", "cookie", "breadcrumb"];
+/// Class or id fragments which rescue an otherwise unlikely element, e.g. "main-menu-content"
+const MAYBE: [&str; 5] = ["article", "body", "column", "main", "story"];
+/// Class or id fragments that suggest an element holds the story
+const POSITIVE: [&str; 9] = ["article", "body", "content", "entry", "main", "page", "post", "text", "story"];
+/// Class or id fragments that suggest an element is beside the story
+const NEGATIVE: [&str; 12] = ["comment", "footnote", "masthead", "media", "meta", "promo", "related", "share", "sidebar", "sponsor", "tags", "widget"];
+/// Paragraphs shorter than this don't get a say in where the story is
+const MIN_PARAGRAPH_LENGTH: usize = 25;
+
+/// An element of the page tree
+#[derive(Debug)]
+struct Element {
+    name: String,
+    /// Only kept for links, every other attribute is dropped
+    href: Option<String>,
+    /// Kept to weigh how likely the element is to hold the story
+    class_and_id: String,
+    parent: Option<usize>,
+    children: Vec<Node>,
+}
+
+#[derive(Debug)]
+enum Node {
+    Element(usize),
+    Text(String),
+}
+
+/// A page parsed into a tree of elements, stored flat with the root first
+struct Document {
+    elements: Vec<Element>,
+}
+
+/// Finds the story in a web page, returning it as simplified HTML, or None if nothing looks like a story
+///
+/// Arguments
+/// * html - The full page
+pub fn extract(html: &str) -> Option<String> {
+    let document = Document::parse(html);
+    let scores = document.score();
+
+    let (best, best_score) = scores
+        .iter()
+        .enumerate()
+        .filter_map(|(index, score)| score.map(|score| (index, score * (1.0 - document.link_density(index)))))
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    // Stories are often split over several containers, pull in the siblings that score nearly as well
+    let threshold = (best_score * 0.2).max(10.0);
+    let siblings: Vec<usize> = match document.elements[best].parent {
+        Some(parent) => document.elements[parent]
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                Node::Element(index) => Some(*index),
+                Node::Text(_) => None,
+            })
+            .filter(|&index| {
+                let score = scores[index].map(|score| score * (1.0 - document.link_density(index)));
+                let text_length = document.text(index).len();
+                index == best
+                    || score.is_some_and(|score| score >= threshold)
+                    || (document.elements[index].name == "p" && text_length > 80 && document.link_density(index) < 0.25)
+            })
+            .collect(),
+        None => vec![best],
+    };
+
+    let mut story = String::new();
+    for index in siblings {
+        document.write(index, &mut story);
+    }
+    Some(story)
+}
+
+impl Document {
+    /// Builds a tree out of a page, forgiving unclosed and stray tags, and dropping page furniture as it goes
+    fn parse(html: &str) -> Document {
+        let root = Element { name: "#root".to_string(), href: None, class_and_id: String::new(), parent: None, children: vec![] };
+        let mut elements = vec![root];
+        let mut open: Vec<usize> = vec![0];
+        // The element being skipped over, and how deeply it is nested inside itself
+        let mut skipping: Option<(String, usize)> = None;
+
+        for token in tokenize(html) {
+            if let Some((skipped, depth)) = &mut skipping {
+                match &token {
+                    HtmlToken::Start { name, self_closing: false, .. } if name == skipped => *depth += 1,
+                    HtmlToken::End(name) if name == skipped => {
+                        *depth -= 1;
+                        if *depth == 0 {
+                            skipping = None;
+                        }
+                    },
+                    _ => {},
+                }
+                continue;
+            }
+
+            match token {
+                HtmlToken::Start { ref name, self_closing, .. } => {
+                    let class_and_id = format!("{} {}", token.attr("class").unwrap_or(""), token.attr("id").unwrap_or("")).to_lowercase();
+                    let is_void = self_closing || VOID.contains(&name.as_str());
+                    if STRIPPED.contains(&name.as_str()) || (is_unlikely(&class_and_id) && name != "body" && name != "a") {
+                        if !is_void {
+                            skipping = Some((name.clone(), 1));
+                        }
+                        continue;
+                    }
+
+                    // A new block ends an open paragraph, and a new list item ends the last one
+                    let top = open.last().copied().unwrap_or(0);
+                    let closes_top = match elements[top].name.as_str() {
+                        "p" => is_block(name),
+                        "li" => name == "li",
+                        _ => false,
+                    };
+                    if closes_top {
+                        open.pop();
+                    }
+
+                    let parent = open.last().copied().unwrap_or(0);
+                    let index = elements.len();
+                    elements.push(Element {
+                        name: name.clone(),
+                        href: token.attr("href").map(|href| href.to_string()),
+                        class_and_id,
+                        parent: Some(parent),
+                        children: vec![],
+                    });
+                    elements[parent].children.push(Node::Element(index));
+                    if !is_void {
+                        open.push(index);
+                    }
+                },
+                HtmlToken::End(name) => {
+                    // Close everything up to the matching element, a closing tag with no match is ignored
+                    if let Some(position) = open.iter().rposition(|&index| index != 0 && elements[index].name == name) {
+                        open.truncate(position);
+                    }
+                },
+                HtmlToken::Text(text) => {
+                    let parent = open.last().copied().unwrap_or(0);
+                    elements[parent].children.push(Node::Text(text));
+                },
+            }
+        }
+        Document { elements }
+    }
+
+    /// Lets every paragraph add to the score of its parent, and half as much to its grandparent.
+    /// Returns the score of each element, None for elements no paragraph voted for
+    fn score(&self) -> Vec<Option<f64>> {
+        let mut scores: Vec<Option<f64>> = vec![None; self.elements.len()];
+        for (index, element) in self.elements.iter().enumerate() {
+            if !matches!(element.name.as_str(), "p" | "pre" | "td" | "blockquote") {
+                continue;
+            }
+            let text = self.text(index);
+            if text.len() < MIN_PARAGRAPH_LENGTH {
+                continue;
+            }
+            let points = 1.0 + text.matches(',').count() as f64 + (text.len() as f64 / 100.0).min(3.0);
+
+            let parent = element.parent.filter(|&parent| parent != 0);
+            let grandparent = parent.and_then(|parent| self.elements[parent].parent).filter(|&grandparent| grandparent != 0);
+            for (ancestor, share) in [(parent, 1.0), (grandparent, 0.5)] {
+                if let Some(ancestor) = ancestor {
+                    let score = scores[ancestor].get_or_insert_with(|| self.initial_score(ancestor));
+                    *score += points * share;
+                }
+            }
+        }
+        scores
+    }
+
+    /// The score an element starts from, based on what kind of element it is and its class and id
+    fn initial_score(&self, index: usize) -> f64 {
+        let element = &self.elements[index];
+        let by_name = match element.name.as_str() {
+            "article" => 10.0,
+            "div" | "main" => 5.0,
+            "pre" | "td" | "blockquote" => 3.0,
+            "ol" | "ul" | "dl" | "dd" | "dt" | "li" => -3.0,
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+            _ => 0.0,
+        };
+        let mut by_class = 0.0;
+        if POSITIVE.iter().any(|word| element.class_and_id.contains(word)) {
+            by_class += 25.0;
+        }
+        if NEGATIVE.iter().any(|word| element.class_and_id.contains(word)) {
+            by_class -= 25.0;
+        }
+        by_name + by_class
+    }
+
+    /// The text inside an element, with runs of whitespace collapsed
+    fn text(&self, index: usize) -> String {
+        let mut raw = String::new();
+        self.collect_text(index, &mut raw);
+        raw.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    fn collect_text(&self, index: usize, text: &mut String) {
+        for child in &self.elements[index].children {
+            match child {
+                Node::Element(child) => self.collect_text(*child, text),
+                Node::Text(content) => {
+                    text.push(' ');
+                    text.push_str(content);
+                },
+            }
+        }
+    }
+
+    /// How much of an element's text is inside links, from 0 to 1. Menus and lists of related stories are mostly links
+    fn link_density(&self, index: usize) -> f64 {
+        let length = self.text(index).len();
+        if length == 0 {
+            return 0.0;
+        }
+        let mut link_length = 0;
+        let mut pending = vec![index];
+        while let Some(current) = pending.pop() {
+            for child in &self.elements[current].children {
+                if let Node::Element(child) = child {
+                    match self.elements[*child].name.as_str() {
+                        "a" => link_length += self.text(*child).len(),
+                        _ => pending.push(*child),
+                    }
+                }
+            }
+        }
+        link_length as f64 / length as f64
+    }
+
+    /// Writes an element back out as HTML, leaving out link farms the scoring let through
+    fn write(&self, index: usize, html: &mut String) {
+        let element = &self.elements[index];
+        let name = element.name.as_str();
+        if matches!(name, "ul" | "ol" | "div" | "section" | "table") && self.link_density(index) > 0.5 {
+            return;
+        }
+
+        html.push('<');
+        html.push_str(name);
+        if let Some(href) = &element.href {
+            html.push_str(" href=\"");
+            html.push_str(&escape(href).replace('"', "&quot;"));
+            html.push('"');
+        }
+        html.push('>');
+        if VOID.contains(&name) {
+            return;
+        }
+        for child in &element.children {
+            match child {
+                Node::Element(child) => self.write(*child, html),
+                Node::Text(text) => html.push_str(&escape(text)),
+            }
+        }
+        html.push_str("</");
+        html.push_str(name);
+        html.push('>');
+    }
+}
+
+/// Whether an element's class and id mark it as page furniture
+fn is_unlikely(class_and_id: &str) -> bool {
+    let words = class_and_id.split(|c: char| !c.is_ascii_alphanumeric());
+    let is_ad = words.into_iter().any(|word| matches!(word, "ad" | "ads" | "advertisement"));
+    (is_ad || UNLIKELY.iter().any(|word| class_and_id.contains(word))) && !MAYBE.iter().any(|word| class_and_id.contains(word))
+}
+
+/// Whether an element starts a new block, which ends any open paragraph
+fn is_block(name: &str) -> bool {
+    matches!(
+        name,
+        "p" | "div" | "section" | "article" | "main" | "ul" | "ol" | "dl" | "table" | "blockquote" | "pre" | "figure" | "hr"
+            | "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+    )
+}
+
+/// Escapes text so it reads back as the same text once tokenized again
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_extract_story(){
+        let story = extract(include_str!("../../resources/fixtures/article.html")).unwrap();
+        assert!(story.contains("The council voted on Tuesday"));
+        assert!(story.contains("Residents who spoke at the meeting"));
+        // Paragraphs split over a sibling container are kept
+        assert!(story.contains("The plan now goes to the province"));
+        // Entities survive being written back out
+        assert!(story.contains("Smith &amp; Sons"));
+        for furniture in ["Home", "Subscribe to our newsletter", "Sponsored", "Most read", "All rights reserved", "Post a comment", "tracker()"] {
+            assert!(!story.contains(furniture), "{} should have been dropped", furniture);
+        }
+    }
+
+    #[test]
+    fn test_nothing_to_extract(){
+        assert_eq!(extract("<html><body><ul><li><a href='/a'>A link</a></li></ul></body></html>"), None);
+        assert_eq!(extract("Not a web page at all"), None);
+    }
+}
@@ -0,0 +1,71 @@
+use std::{collections::HashSet, path::PathBuf};
+use log::warn;
+use super::storage::{read_json, write_json};
+
+/// The ids of every article the user has read, kept on disk between sessions
+#[derive(Debug, Clone, Default)]
+pub struct ReadArticles {
+    path: Option<PathBuf>,
+    ids: HashSet<String>,
+}
+
+impl ReadArticles {
+    /// Loads the read articles kept in the given file, starting empty if there is none
+    pub fn load(path: PathBuf) -> ReadArticles {
+        let ids = read_json(&path).unwrap_or_default();
+        ReadArticles { path: Some(path), ids }
+    }
+
+    /// Creates a set of read articles which is never saved
+    pub fn disabled() -> ReadArticles {
+        ReadArticles::default()
+    }
+
+    pub fn is_read(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    /// Marks an article as read or unread, saving the change straight away
+    ///
+    /// Arguments
+    /// * id - The id of the article
+    /// * read - Whether the article has been read
+    pub fn set_read(&mut self, id: &str, read: bool) {
+        let changed = if read {
+            self.ids.insert(id.to_string())
+        } else {
+            self.ids.remove(id)
+        };
+        if changed {
+            self.save();
+        }
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else { return };
+        if let Err(e) = write_json(path, &self.ids) {
+            warn!("Couldn't save read articles to {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::newsroomcore::storage::TempPath;
+
+    #[test]
+    fn test_read_persists(){
+        let path = TempPath::new("read.json");
+
+        let mut read = ReadArticles::load(path.to_path_buf());
+        assert!(!read.is_read("a"));
+        read.set_read("a", true);
+        read.set_read("b", true);
+        read.set_read("b", false);
+
+        let reloaded = ReadArticles::load(path.to_path_buf());
+        assert!(reloaded.is_read("a"));
+        assert!(!reloaded.is_read("b"));
+    }
+}
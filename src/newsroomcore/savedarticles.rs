@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+use log::warn;
+use super::{newsarticle::NewsArticle, storage::{read_json, write_json}};
+
+/// Articles the user starred to keep for later, stored in full so they outlive their feed
+#[derive(Debug, Clone, Default)]
+pub struct SavedArticles {
+    path: Option<PathBuf>,
+    articles: Vec<NewsArticle>,
+}
+
+impl SavedArticles {
+    /// Loads the saved articles kept in the given file, starting empty if there is none
+    pub fn load(path: PathBuf) -> SavedArticles {
+        let articles = read_json(&path).unwrap_or_default();
+        SavedArticles { path: Some(path), articles }
+    }
+
+    /// Creates a set of saved articles which is never written to disk
+    pub fn disabled() -> SavedArticles {
+        SavedArticles::default()
+    }
+
+    /// The saved articles, most recently saved first
+    pub fn articles(&self) -> &[NewsArticle] {
+        &self.articles
+    }
+
+    pub fn is_saved(&self, id: &str) -> bool {
+        self.articles.iter().any(|article| article.id == id)
+    }
+
+    /// Saves an article, or removes it if it is already saved, writing the change straight away
+    ///
+    /// Arguments
+    /// * article - The article to save or remove
+    pub fn toggle(&mut self, article: &NewsArticle) {
+        match self.articles.iter().position(|saved| saved.id == article.id) {
+            Some(index) => { self.articles.remove(index); },
+            None => self.articles.insert(0, article.clone()),
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else { return };
+        if let Err(e) = write_json(path, &self.articles) {
+            warn!("Couldn't save starred articles to {}: {}", path.display(), e);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::cmp::Reverse;
+use serde::{de::{IntoDeserializer, value::{Error, StrDeserializer}}, Deserialize, Serialize};
+use super::{newsarticle::NewsArticle, storage::stable_hash};
+
+/// How articles are ordered and grouped, set with `sort` in newsroom.toml and cycled from the news tab
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortMode {
+    /// Most recently published first, undated articles last
+    #[default]
+    #[serde(alias = "date")]
+    Newest,
+    /// Under a header per source, alphabetically by source and newest first within each
+    #[serde(alias = "source")]
+    Grouped,
+    /// Newest from each source in turn, so one prolific source can't crowd out the rest
+    RoundRobin,
+    /// A shuffle which is the same every time, so refreshing doesn't reorder articles already on screen
+    Shuffled,
+}
+
+impl SortMode {
+    pub const ALL: [SortMode; 4] = [SortMode::Newest, SortMode::Grouped, SortMode::RoundRobin, SortMode::Shuffled];
+
+    /// The mode's name as written in newsroom.toml
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortMode::Newest => "newest",
+            SortMode::Grouped => "grouped",
+            SortMode::RoundRobin => "round-robin",
+            SortMode::Shuffled => "shuffled",
+        }
+    }
+
+    /// Looks a mode up by the name it is given in newsroom.toml, aliases included
+    ///
+    /// Arguments
+    /// * name - The name of the mode
+    pub fn from_name(name: &str) -> Option<SortMode> {
+        let deserializer: StrDeserializer<Error> = name.into_deserializer();
+        SortMode::deserialize(deserializer).ok()
+    }
+
+    /// The mode after this one, wrapping around
+    pub fn next(self) -> SortMode {
+        match self {
+            SortMode::Newest => SortMode::Grouped,
+            SortMode::Grouped => SortMode::RoundRobin,
+            SortMode::RoundRobin => SortMode::Shuffled,
+            SortMode::Shuffled => SortMode::Newest,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortMode::Newest => "Newest",
+            SortMode::Grouped => "By source",
+            SortMode::RoundRobin => "Round robin",
+            SortMode::Shuffled => "Shuffled",
+        }
+    }
+
+    /// Sorts the articles in place
+    ///
+    /// Arguments
+    /// * articles - The articles to sort
+    pub fn sort(&self, articles: &mut [NewsArticle]) {
+        match self {
+            SortMode::Newest => articles.sort_by_key(|article| Reverse(article.published)),
+            SortMode::Grouped => sort_by_source(articles),
+            SortMode::RoundRobin => {
+                sort_by_source(articles);
+                // Rank each article by how many newer ones its source has, then take every source's
+                // first article, then every source's second, and so on
+                let mut rounds: Vec<(usize, NewsArticle)> = Vec::with_capacity(articles.len());
+                for article in articles.iter() {
+                    let round = match rounds.last() {
+                        Some((round, previous)) if previous.source.url == article.source.url => round + 1,
+                        _ => 0,
+                    };
+                    rounds.push((round, article.clone()));
+                }
+                rounds.sort_by_key(|(round, _)| *round);
+                for (slot, (_, article)) in articles.iter_mut().zip(rounds) {
+                    *slot = article;
+                }
+            },
+            // Hashing the id acts as a fixed seed, an article always lands in the same place relative to the others
+            SortMode::Shuffled => articles.sort_by_key(|article| stable_hash(&article.id)),
+        }
+    }
+}
+
+/// Sorts alphabetically by source, newest first within each source. Sources sharing a name are told apart by their url
+fn sort_by_source(articles: &mut [NewsArticle]) {
+    articles.sort_by(|a, b| {
+        a.source.name.cmp(&b.source.name)
+            .then_with(|| a.source.url.cmp(&b.source.url))
+            .then_with(|| b.published.cmp(&a.published))
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::DateTime;
+    use super::*;
+    use crate::newsroomcore::datasources::DataSources;
+
+    fn article(source: &DataSources, id: &str, published: Option<&str>) -> NewsArticle {
+        NewsArticle {
+            published: published.and_then(|date| DateTime::parse_from_rfc3339(date).ok()),
+            ..NewsArticle::test(source, id)
+        }
+    }
+
+    fn ids(articles: &[NewsArticle]) -> Vec<&str> {
+        articles.iter().map(|article| article.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_sort_modes(){
+        let a = DataSources::new("a", "https://a.example.com");
+        let b = DataSources::new("b", "https://b.example.com");
+        let mut articles = vec![
+            article(&b, "old", Some("2023-10-01T00:00:00Z")),
+            article(&a, "undated", None),
+            article(&a, "new", Some("2023-10-03T00:00:00+02:00")),
+            article(&b, "newer", Some("2023-10-03T01:00:00Z")),
+        ];
+
+        SortMode::Newest.sort(&mut articles);
+        assert_eq!(ids(&articles), vec!["newer", "new", "old", "undated"]);
+
+        SortMode::Grouped.sort(&mut articles);
+        assert_eq!(ids(&articles), vec!["new", "undated", "newer", "old"]);
+
+        SortMode::RoundRobin.sort(&mut articles);
+        assert_eq!(ids(&articles), vec!["new", "newer", "undated", "old"]);
+
+        // The shuffle doesn't depend on the order it started from
+        SortMode::Shuffled.sort(&mut articles);
+        let shuffled: Vec<String> = ids(&articles).iter().map(|id| id.to_string()).collect();
+        articles.reverse();
+        SortMode::Shuffled.sort(&mut articles);
+        assert_eq!(ids(&articles), shuffled);
+
+        // Two sources with the same name are still two groups
+        let other_a = DataSources::new("a", "https://other.example.com");
+        let mut articles = vec![
+            article(&a, "a-old", Some("2023-10-01T00:00:00Z")),
+            article(&other_a, "other-new", Some("2023-10-04T00:00:00Z")),
+            article(&a, "a-new", Some("2023-10-03T00:00:00Z")),
+            article(&other_a, "other-old", Some("2023-10-02T00:00:00Z")),
+        ];
+        SortMode::Grouped.sort(&mut articles);
+        assert_eq!(ids(&articles), vec!["a-new", "a-old", "other-new", "other-old"]);
+        SortMode::RoundRobin.sort(&mut articles);
+        assert_eq!(ids(&articles), vec!["a-new", "other-new", "a-old", "other-old"]);
+    }
+}
@@ -0,0 +1,100 @@
+// Helpers for the files newsroom keeps on disk between sessions
+use std::{error::Error, fs, path::Path};
+use log::warn;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Hashes text into a number which is stable across runs, versions and platforms
+/// (unlike std's DefaultHasher) so it is safe to use in file names and persisted ids. 64 bit FNV-1a
+pub(crate) fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Reads a JSON file, returning None if it is missing or unreadable
+///
+/// Arguments
+/// * path - The file to read
+pub(crate) fn read_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let content = fs::read(path).ok()?;
+    match serde_json::from_slice(&content) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("Ignoring unreadable file {}: {}", path.display(), e);
+            None
+        },
+    }
+}
+
+/// Writes a value to a JSON file, creating any missing directories
+///
+/// Arguments
+/// * path - The file to write
+/// * value - The value to store
+pub(crate) fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(fs::write(path, serde_json::to_vec(value)?)?)
+}
+
+/// A file or directory in the system's temp directory for a test to use, removed again when dropped
+/// so a failing assert doesn't leave it behind for the next run
+#[cfg(test)]
+pub(crate) struct TempPath(std::path::PathBuf);
+
+#[cfg(test)]
+impl TempPath {
+    /// Clears anything a previous run left at the path
+    ///
+    /// Arguments
+    /// * name - The file or directory name, made unique to this process
+    pub(crate) fn new(name: &str) -> TempPath {
+        let path = TempPath(std::env::temp_dir().join(format!("newsroom-test-{}-{}", std::process::id(), name)));
+        path.remove();
+        path
+    }
+
+    fn remove(&self) {
+        let _ = match self.0.is_dir() {
+            true => fs::remove_dir_all(&self.0),
+            false => fs::remove_file(&self.0),
+        };
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TempPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl AsRef<Path> for TempPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stable_hash(){
+        // Known FNV-1a values, these must never change or persisted data is orphaned
+        assert_eq!(stable_hash(""), 0xcbf29ce484222325);
+        assert_eq!(stable_hash("a"), 0xaf63dc4c8601ec8c);
+        assert_ne!(stable_hash("https://example.com/a"), stable_hash("https://example.com/b"));
+    }
+}
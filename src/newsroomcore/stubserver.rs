@@ -0,0 +1,120 @@
+// A tiny HTTP server for tests, so fetching can be exercised without touching the network
+use std::{collections::HashMap, sync::{Arc, Mutex}, time::Duration};
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::{TcpListener, TcpStream}};
+
+/// A canned response served by the stub server
+#[derive(Debug, Clone)]
+pub(crate) struct StubResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    delay: Duration,
+}
+
+impl StubResponse {
+    /// A 200 response with the given body
+    pub fn ok(body: impl Into<Vec<u8>>) -> StubResponse {
+        StubResponse { status: 200, headers: vec![], body: body.into(), delay: Duration::ZERO }
+    }
+
+    /// An empty response with the given status
+    pub fn status(status: u16) -> StubResponse {
+        StubResponse { status, headers: vec![], body: vec![], delay: Duration::ZERO }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> StubResponse {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Wait before responding, to simulate a slow server
+    pub fn delay(mut self, delay: Duration) -> StubResponse {
+        self.delay = delay;
+        self
+    }
+}
+
+/// A request received by the stub server
+#[derive(Debug, Clone)]
+pub(crate) struct StubRequest {
+    pub path: String,
+    /// Header names are lowercased
+    pub headers: HashMap<String, String>,
+}
+
+/// Serves canned responses on a local port. Each path serves its responses in order,
+/// repeating the last one once they run out. Unknown paths get a 404
+pub(crate) struct StubServer {
+    base: String,
+    requests: Arc<Mutex<Vec<StubRequest>>>,
+}
+
+impl StubServer {
+    pub async fn start(routes: Vec<(&str, Vec<StubResponse>)>) -> StubServer {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests: Arc<Mutex<Vec<StubRequest>>> = Arc::new(Mutex::new(vec![]));
+        let routes: Arc<Mutex<HashMap<String, Vec<StubResponse>>>> = Arc::new(Mutex::new(
+            routes.into_iter().map(|(path, responses)| (path.to_string(), responses)).collect()
+        ));
+
+        let served_requests = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(StubServer::serve(stream, routes.clone(), served_requests.clone()));
+            }
+        });
+
+        StubServer { base, requests }
+    }
+
+    async fn serve(mut stream: TcpStream, routes: Arc<Mutex<HashMap<String, Vec<StubResponse>>>>, requests: Arc<Mutex<Vec<StubRequest>>>) {
+        // Read until the end of the request head, test requests never carry a body
+        let mut head: Vec<u8> = vec![];
+        let mut chunk = [0u8; 1024];
+        while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return,
+                Ok(read) => head.extend_from_slice(&chunk[..read]),
+            }
+        }
+        let head = String::from_utf8_lossy(&head).to_string();
+        let path = head.lines().next().and_then(|line| line.split_whitespace().nth(1)).unwrap_or("/").to_string();
+        let headers = head
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+            .collect();
+        requests.lock().unwrap().push(StubRequest { path: path.clone(), headers });
+
+        let response = {
+            let mut routes = routes.lock().unwrap();
+            match routes.get_mut(&path) {
+                Some(responses) if responses.len() > 1 => responses.remove(0),
+                Some(responses) if !responses.is_empty() => responses[0].clone(),
+                _ => StubResponse::status(404),
+            }
+        };
+
+        tokio::time::sleep(response.delay).await;
+        let mut raw = format!("HTTP/1.1 {} Stub\r\nContent-Length: {}\r\nConnection: close\r\n", response.status, response.body.len());
+        for (name, value) in &response.headers {
+            raw.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        raw.push_str("\r\n");
+        let _ = stream.write_all(raw.as_bytes()).await;
+        let _ = stream.write_all(&response.body).await;
+        let _ = stream.shutdown().await;
+    }
+
+    /// The url of a path on this server
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base, path)
+    }
+
+    /// Every request received so far, oldest first
+    pub fn requests(&self) -> Vec<StubRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
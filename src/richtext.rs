@@ -0,0 +1,400 @@
+// Turns the HTML feeds embed in their articles into styled terminal text
+use tui::prelude::*;
+
+use crate::{newsroomcore::html::{tokenize, HtmlToken}, settings::Theme};
+
+/// Formatted text ready to render, along with the links it referred to
+#[derive(Debug, Clone, Default)]
+pub struct RichText {
+    pub lines: Vec<Line<'static>>,
+    /// Link targets, numbered from 1 by the footnote markers in the text
+    pub links: Vec<String>,
+}
+
+impl RichText {
+    /// Renders an article body, which may be HTML or plain text. Links are numbered as footnotes
+    /// and listed at the end
+    ///
+    /// Arguments
+    /// * content - The HTML or plain text to render
+    /// * theme - The theme to style the text with
+    pub fn from_html(content: &str, theme: &Theme) -> RichText {
+        let mut renderer = Renderer::new(theme);
+        if content.contains('<') {
+            for token in tokenize(content) {
+                renderer.token(token);
+            }
+        } else {
+            renderer.plain_text(content);
+        }
+        renderer.finish()
+    }
+
+    /// How many rows the text takes up once wrapped to a width
+    ///
+    /// Arguments
+    /// * width - The width the text is wrapped to
+    pub fn height(&self, width: u16) -> usize {
+        self.lines
+            .iter()
+            .map(|line| {
+                let text: String = line.spans.iter().map(|span| span.content.as_ref()).collect();
+                textwrap::wrap(&text, width.max(1) as usize).len().max(1)
+            })
+            .sum()
+    }
+}
+
+/// Walks the tokens of a document, building up lines as blocks open and close
+struct Renderer<'t> {
+    theme: &'t Theme,
+    lines: Vec<Line<'static>>,
+    /// The line being built
+    spans: Vec<Span<'static>>,
+    links: Vec<String>,
+    /// Footnote numbers of the links currently open
+    open_links: Vec<Option<usize>>,
+    /// The inline elements currently open and the style each adds to its text
+    marks: Vec<(String, Style)>,
+    /// The lists currently open, None for bullets or the next number for numbered lists
+    lists: Vec<Option<usize>>,
+    /// The marker waiting to start the first line of a list item
+    bullet: Option<String>,
+    quote_depth: usize,
+    heading: bool,
+    preformatted: bool,
+    /// How deep we are inside elements whose content is never shown
+    hidden: usize,
+    /// Whitespace was collapsed and a space is owed before the next word
+    pending_space: bool,
+}
+
+impl<'t> Renderer<'t> {
+    fn new(theme: &'t Theme) -> Renderer<'t> {
+        Renderer {
+            theme,
+            lines: vec![],
+            spans: vec![],
+            links: vec![],
+            open_links: vec![],
+            marks: vec![],
+            lists: vec![],
+            bullet: None,
+            quote_depth: 0,
+            heading: false,
+            preformatted: false,
+            hidden: 0,
+            pending_space: false,
+        }
+    }
+
+    /// Renders text which has no markup, keeping its paragraphs and line breaks
+    fn plain_text(&mut self, text: &str) {
+        for paragraph in text.split("\n\n") {
+            for line in paragraph.lines() {
+                self.text(line);
+                self.line_break();
+            }
+            self.paragraph_break();
+        }
+    }
+
+    fn token(&mut self, token: HtmlToken) {
+        match token {
+            HtmlToken::Text(text) if self.hidden == 0 => self.text(&text),
+            HtmlToken::Text(_) => {},
+            HtmlToken::Start { name, attrs, self_closing } => {
+                let href = attrs.iter().find(|(attr, _)| attr == "href").map(|(_, value)| value.clone());
+                self.start(&name, href);
+                if self_closing {
+                    self.end(&name);
+                }
+            },
+            HtmlToken::End(name) => self.end(&name),
+        }
+    }
+
+    fn start(&mut self, name: &str, href: Option<String>) {
+        match name {
+            "script" | "style" | "head" | "template" | "noscript" | "svg" | "iframe" | "video" | "audio" | "object" => self.hidden += 1,
+            "p" | "table" | "figure" | "dl" => self.paragraph_break(),
+            "div" | "section" | "article" | "header" | "footer" | "main" | "aside" | "tr" | "dt" | "dd" | "figcaption" => self.line_break(),
+            "br" => self.force_line_break(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.paragraph_break();
+                self.heading = true;
+            },
+            "blockquote" => {
+                self.paragraph_break();
+                self.quote_depth += 1;
+            },
+            "pre" => {
+                self.paragraph_break();
+                self.preformatted = true;
+            },
+            "hr" => {
+                self.paragraph_break();
+                self.spans.push(Span::styled("────────", self.theme.description));
+                self.paragraph_break();
+            },
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.paragraph_break();
+                } else {
+                    self.line_break();
+                }
+                self.lists.push(if name == "ol" { Some(1) } else { None });
+            },
+            "li" => {
+                self.line_break();
+                self.bullet = Some(match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    },
+                    _ => "• ".to_string(),
+                });
+            },
+            "a" => {
+                // Only real links get a footnote, not in page anchors or javascript
+                let footnote = href
+                    .filter(|href| href.starts_with("http://") || href.starts_with("https://"))
+                    .map(|href| self.footnote(href));
+                self.open_links.push(footnote);
+                let style = if footnote.is_some() { self.theme.link } else { Style::default() };
+                self.marks.push((name.to_string(), style));
+            },
+            "b" | "strong" => self.marks.push((name.to_string(), Style::new().add_modifier(Modifier::BOLD))),
+            "i" | "em" | "cite" => self.marks.push((name.to_string(), Style::new().add_modifier(Modifier::ITALIC))),
+            "u" | "ins" => self.marks.push((name.to_string(), Style::new().add_modifier(Modifier::UNDERLINED))),
+            "s" | "del" | "strike" => self.marks.push((name.to_string(), Style::new().add_modifier(Modifier::CROSSED_OUT))),
+            "code" | "kbd" | "samp" | "tt" => self.marks.push((name.to_string(), self.theme.code)),
+            _ => {},
+        }
+    }
+
+    fn end(&mut self, name: &str) {
+        match name {
+            "script" | "style" | "head" | "template" | "noscript" | "svg" | "iframe" | "video" | "audio" | "object" => self.hidden = self.hidden.saturating_sub(1),
+            "p" | "table" | "figure" | "dl" => self.paragraph_break(),
+            "div" | "section" | "article" | "header" | "footer" | "main" | "aside" | "tr" | "dt" | "dd" | "figcaption" | "li" => self.line_break(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.heading = false;
+                self.paragraph_break();
+            },
+            "blockquote" => {
+                self.paragraph_break();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            },
+            "pre" => {
+                self.preformatted = false;
+                self.paragraph_break();
+            },
+            "ul" | "ol" => {
+                self.lists.pop();
+                self.bullet = None;
+                if self.lists.is_empty() {
+                    self.paragraph_break();
+                } else {
+                    self.line_break();
+                }
+            },
+            "a" => {
+                self.close_mark(name);
+                if let Some(Some(number)) = self.open_links.pop() {
+                    self.spans.push(Span::styled(format!("[{}]", number), self.theme.description));
+                }
+            },
+            "b" | "strong" | "i" | "em" | "cite" | "u" | "ins" | "s" | "del" | "strike" | "code" | "kbd" | "samp" | "tt" => self.close_mark(name),
+            _ => {},
+        }
+    }
+
+    /// Drops the style of the innermost open element with this name. Stray closing tags are ignored
+    fn close_mark(&mut self, name: &str) {
+        if let Some(index) = self.marks.iter().rposition(|(open, _)| open == name) {
+            self.marks.remove(index);
+        }
+    }
+
+    /// Numbers a link, reusing the number if the same link appeared before
+    fn footnote(&mut self, href: String) -> usize {
+        match self.links.iter().position(|link| *link == href) {
+            Some(index) => index + 1,
+            None => {
+                self.links.push(href);
+                self.links.len()
+            },
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.preformatted {
+            for (i, line) in text.split('\n').enumerate() {
+                if i > 0 {
+                    self.force_line_break();
+                }
+                if !line.is_empty() {
+                    self.start_line();
+                    self.spans.push(Span::styled(line.to_string(), self.text_style()));
+                }
+            }
+            return;
+        }
+
+        // Collapse runs of whitespace into single spaces, dropping them at the start of a line
+        let mut collapsed = String::new();
+        for c in text.chars() {
+            if c.is_whitespace() {
+                self.pending_space = true;
+                continue;
+            }
+            if self.pending_space && collapsed.is_empty() && !self.spans.is_empty() {
+                // A space owed from before this element shouldn't pick up its underline or highlight
+                self.spans.push(Span::raw(" "));
+            } else if self.pending_space && !collapsed.is_empty() {
+                collapsed.push(' ');
+            }
+            self.pending_space = false;
+            collapsed.push(c);
+        }
+        if !collapsed.is_empty() {
+            self.start_line();
+            self.spans.push(Span::styled(collapsed, self.text_style()));
+        }
+    }
+
+    /// The style of text at this point, combining the block it is in with every open inline element
+    fn text_style(&self) -> Style {
+        let base = match (self.heading, self.preformatted) {
+            (true, _) => self.theme.description_title,
+            (_, true) => self.theme.code,
+            _ => Style::default(),
+        };
+        self.marks.iter().fold(base, |style, (_, mark)| style.patch(*mark))
+    }
+
+    /// Adds the quote bars, list indentation and bullet that begin a line, if the line is still empty
+    fn start_line(&mut self) {
+        if !self.spans.is_empty() {
+            return;
+        }
+        if self.quote_depth > 0 {
+            self.spans.push(Span::styled("│ ".repeat(self.quote_depth), self.theme.description));
+        }
+        if !self.lists.is_empty() {
+            let indent = "  ".repeat(self.lists.len() - 1);
+            // Lines after the first in a list item line up with the text after the bullet
+            let marker = self.bullet.take().unwrap_or_else(|| "  ".to_string());
+            self.spans.push(Span::raw(format!("{}{}", indent, marker)));
+        }
+    }
+
+    /// Ends the current line if it has anything on it
+    fn line_break(&mut self) {
+        if !self.spans.is_empty() {
+            self.lines.push(Line::from(std::mem::take(&mut self.spans)));
+        }
+        self.pending_space = false;
+    }
+
+    /// Ends the current line even if it is empty, as `<br>` does
+    fn force_line_break(&mut self) {
+        self.lines.push(Line::from(std::mem::take(&mut self.spans)));
+        self.pending_space = false;
+    }
+
+    /// Ends the current line and leaves a blank line before whatever follows
+    fn paragraph_break(&mut self) {
+        self.line_break();
+        if self.lines.last().is_some_and(|line| !line.spans.is_empty()) {
+            self.lines.push(Line::default());
+        }
+    }
+
+    fn finish(mut self) -> RichText {
+        self.line_break();
+        while self.lines.last().is_some_and(|line| line.spans.is_empty()) {
+            self.lines.pop();
+        }
+
+        if !self.links.is_empty() {
+            self.lines.push(Line::default());
+            self.lines.push(Line::styled("Links", self.theme.description_title));
+            for (i, link) in self.links.iter().enumerate() {
+                self.lines.push(Line::styled(format!("[{}] {}", i + 1, link), self.theme.description));
+            }
+        }
+        RichText { lines: self.lines, links: self.links }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn plain(text: &RichText) -> Vec<String> {
+        text.lines
+            .iter()
+            .map(|line| line.spans.iter().map(|span| span.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_blocks(){
+        let html = r##"
+            <h2>Big   news</h2>
+            <p>First <a href="https://example.com/a">para</a>graph.</p>
+            <blockquote><p>Quoted</p></blockquote>
+            <ul><li>One</li><li>Two<ol><li>Nested</li></ol></li></ul>
+            <script>alert("hidden")</script>
+            <p>Line<br>break, see <a href="https://example.com/a">again</a> and <a href="#top">top</a></p>
+        "##;
+        let text = RichText::from_html(html, &Theme::new());
+        assert_eq!(plain(&text), vec![
+            "Big news",
+            "",
+            "First para[1]graph.",
+            "",
+            "│ Quoted",
+            "",
+            "• One",
+            "• Two",
+            "  1. Nested",
+            "",
+            "Line",
+            "break, see again[1] and top",
+            "",
+            "Links",
+            "[1] https://example.com/a",
+        ]);
+        assert_eq!(text.links, vec!["https://example.com/a"]);
+    }
+
+    #[test]
+    fn test_inline_styles(){
+        let theme = Theme::new();
+        let html = r#"<p><b>Bold <i>both</i></b> <a href="https://example.com">link</a><img src="x.png" alt="A photo"> <code>x &lt; 1</code></p>"#;
+        let text = RichText::from_html(html, &theme);
+        let spans = &text.lines[0].spans;
+        let styled: Vec<(&str, Style)> = spans.iter().map(|span| (span.content.as_ref(), span.style)).collect();
+        assert_eq!(styled, vec![
+            ("Bold", Style::new().add_modifier(Modifier::BOLD)),
+            (" ", Style::default()),
+            ("both", Style::new().add_modifier(Modifier::BOLD | Modifier::ITALIC)),
+            (" ", Style::default()),
+            ("link", theme.link),
+            ("[1]", theme.description),
+            (" ", Style::default()),
+            ("x < 1", theme.code),
+        ]);
+    }
+
+    #[test]
+    fn test_plain_text(){
+        let text = RichText::from_html("A teaser\nwith two lines\n\nand a second paragraph", &Theme::new());
+        assert_eq!(plain(&text), vec!["A teaser", "with two lines", "", "and a second paragraph"]);
+        // "with two lines" and "and a second paragraph" both wrap at 10 columns
+        assert_eq!(text.height(10), 7);
+    }
+}
@@ -3,7 +3,7 @@ use std::rc::Rc;
 use itertools::Itertools;
 use tui::{prelude::*, widgets::*};
 
-use crate::{tabs::*, app::App};
+use crate::{tabs::*, app::App, keymap::Action};
 
 /// Struct to store root ui
 pub struct Root<'a> {
@@ -23,6 +23,9 @@ impl Widget for Root<'_> {
         let area = layout(area, Direction::Vertical, vec![1, 0, 1]);
         self.render_title_bar(area[0], buf);
         self.render_selected_tab(area[1], buf);
+        if let Some(help) = &self.context.help {
+            HelpView::new(help, &self.context.settings.theme).render(area[1], buf);
+        }
         self.render_bottom_bar(area[2], buf);
     }
 }
@@ -30,10 +33,10 @@ impl Widget for Root<'_> {
 impl Root<'_> {
     /// Render the title bar of the app
     fn render_title_bar(&self, area: Rect, buf: &mut Buffer) {
-        let area = layout(area, Direction::Horizontal, vec![0, 20]);
+        let area = layout(area, Direction::Horizontal, vec![0, 27]);
 
         Paragraph::new(Span::styled("Newsroom", self.context.settings.theme.app_title)).render(area[0], buf);
-        let titles = vec![" News ", " Settings "];
+        let titles = vec![" News ", " Saved ", " Settings "];
         Tabs::new(titles)
             .style(self.context.settings.theme.tabs)
             .highlight_style(self.context.settings.theme.tabs_selected)
@@ -43,21 +46,114 @@ impl Root<'_> {
     }
     /// Render the main area according to the selected tab
     fn render_selected_tab(&self, area: Rect, buf: &mut Buffer) {
+        if let Some(reader) = &self.context.reader {
+            ReaderView::new(reader, &self.context.settings.theme).render(area, buf);
+            return;
+        }
         match self.context.tab {
-            0 => NewsTab::new(&self.context.newsroom_state, self.context.settings.theme.clone(), self.context.row).render(area, buf),
-            1 => SettingsTab::new(self.context.settings.clone()).render(area, buf),
+            0 => NewsTab::new(self.context).render(area, buf),
+            1 => SavedTab::new(self.context).render(area, buf),
+            2 => SettingsTab::new(self.context).render(area, buf),
             _ => unreachable!(),
         };
+        if let Some(form) = &self.context.form {
+            FormView::new(form, &self.context.settings.theme, &self.context.settings.keymap).render(area, buf);
+        }
     }
     /// Render the text on the bottom bar
     fn render_bottom_bar(&self, area: Rect, buf: &mut Buffer) {
-        let keys = [
-            ("Q/Esc", "Quit"),
-            ("Tab", "Next Tab"),
-            ("↑/k", "Up"),
-            ("↓/j", "Down"),
-            ("↵", "Open article")
+        let theme = &self.context.settings.theme;
+        if let Some(command) = &self.context.command {
+            let mut spans = vec![Span::styled(format!(":{}█", command.input), theme.content)];
+            match &command.error {
+                Some(error) => spans.push(Span::styled(format!("  {}", error), theme.error)),
+                None if !command.completions.is_empty() => spans.push(Span::styled(format!("  {}", command.completions.join("  ")), theme.keybinding.description)),
+                None => {},
+            }
+            Paragraph::new(Line::from(spans)).style(theme.keybinding.description).render(area, buf);
+            return;
+        }
+        if let Some((status, _)) = &self.context.status {
+            Paragraph::new(status.as_str())
+                .alignment(Alignment::Center)
+                .style(theme.keybinding.description)
+                .render(area, buf);
+            return;
+        }
+        let sort = format!("Sort: {}", self.context.settings.sort.label());
+        let reader_keys = [
+            (Action::Close, "Back"),
+            (Action::Help, "Help"),
+            (Action::Up, "Up"),
+            (Action::Down, "Down"),
+            (Action::PageUp, "Page up"),
+            (Action::PageDown, "Page down"),
+            (Action::OpenInBrowser, "Open in browser"),
+            (Action::Quit, "Quit"),
+        ];
+        let list_keys = [
+            (Action::Quit, "Quit"),
+            (Action::Help, "Help"),
+            (Action::Command, "Command"),
+            (Action::NextTab, "Next Tab"),
+            (Action::Up, "Up"),
+            (Action::Down, "Down"),
+            (Action::Open, "Read"),
+            (Action::OpenInBrowser, "Browser"),
+            (Action::ToggleRead, "Read/Unread"),
+            (Action::ToggleSaved, "Save"),
+            (Action::CycleSort, sort.as_str()),
+            (Action::Search, "Search"),
+            (Action::ToggleSidebar, "Sources"),
+        ];
+        let settings_keys = [
+            (Action::Quit, "Quit"),
+            (Action::Help, "Help"),
+            (Action::Command, "Command"),
+            (Action::NextTab, "Next Tab"),
+            (Action::AddSource, "Add"),
+            (Action::EditSource, "Edit"),
+            (Action::DeleteSource, "Delete"),
+            (Action::ToggleSource, "On/Off"),
+            (Action::MoveSourceUp, "Move up"),
+            (Action::MoveSourceDown, "Move down"),
+            (Action::ImportOpml, "Import OPML"),
+            (Action::ExportOpml, "Export OPML"),
+            (Action::PickTheme, "Theme"),
+        ];
+        let help_keys = [
+            (Action::Up, "Up"),
+            (Action::Down, "Down"),
+            (Action::PageUp, "Page up"),
+            (Action::PageDown, "Page down"),
+            (Action::Help, "Close"),
         ];
+        let theme_keys = [
+            (Action::Up, "Previous theme"),
+            (Action::Down, "Next theme"),
+            (Action::ConfirmTheme, "Keep"),
+            (Action::CancelTheme, "Revert"),
+        ];
+        let form_keys = [
+            (Action::SubmitForm, "Confirm"),
+            (Action::CancelForm, "Cancel"),
+            (Action::NextField, "Next field"),
+        ];
+        let keymap = &self.context.settings.keymap;
+        // Only actions which still have keys are listed
+        let bound = |keys: &[(Action, &str)]| {
+            keys.iter()
+                .filter_map(|(action, desc)| keymap.label(*action).map(|label| (label, desc.to_string())))
+                .collect_vec()
+        };
+        let keys = match (&self.context.reader, &self.context.form) {
+            _ if self.context.help.is_some() => bound(&help_keys),
+            (_, Some(_)) => bound(&form_keys),
+            (Some(_), _) => bound(&reader_keys),
+            _ if self.context.theme_picker.is_some() => bound(&theme_keys),
+            _ if self.context.tab == 2 => bound(&settings_keys),
+            _ => bound(&list_keys),
+        };
         let spans = keys
             .iter()
             .flat_map(|(key, desc)| {
@@ -68,8 +164,7 @@ impl Root<'_> {
             .collect_vec();
         Paragraph::new(Line::from(spans))
             .alignment(Alignment::Center)
-            .fg(Color::Indexed(236))
-            .bg(Color::Indexed(232))
+            .style(self.context.settings.theme.keybinding.description)
             .render(area, buf);
     }
 }
@@ -1,24 +1,68 @@
-use std::{fs, error::Error};
+use std::{collections::BTreeMap, fs, error::Error, io::ErrorKind, path::{Path, PathBuf}};
 use log::{warn, info};
 
-use tui::style::{Style, Color, Modifier};
 use toml;
 use serde::{Deserialize, Serialize};
-use crate::newsroomcore::datasources::DataSources;
+use crate::newsroomcore::{datasources::DataSources, fetchpolicy::FetchPolicy, opml::{read_opml, write_opml}, sortmode::SortMode};
 use std::env;
+pub use crate::theme::{Theme, KeyBinding, ThemeConfig, BUILTIN_THEMES};
+use crate::keymap::{Keymap, KeyList};
 
 /// Struct to store primary settings used for the app
 /// Contains both news sources and theme info
 #[derive(Debug, Clone)]
 pub struct Settings {
     pub theme: Theme,
+    /// Themes defined in newsroom.toml, offered alongside the built in ones
+    pub custom_themes: BTreeMap<String, ThemeConfig>,
+    pub keymap: Keymap,
+    /// Problems found in newsroom.toml, reported when the app starts
+    pub problems: Vec<String>,
     pub sources: Vec<DataSources>,
+    pub fetch: FetchPolicy,
+    /// How the news list is ordered and grouped
+    pub sort: SortMode,
+    /// The newsroom.toml these settings were read from and are saved back to, None if they are never saved
+    pub(crate) config_file: Option<PathBuf>,
+}
+
+/// What importing an OPML file did
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpmlImport {
+    /// How many new sources were added
+    pub added: usize,
+    /// The entries which weren't valid sources, each with the reason it was left out
+    pub skipped: Vec<String>,
+}
+
+impl OpmlImport {
+    /// Describes the import in a line, like "Imported 3 new sources from feeds.opml, skipped 1: ..."
+    ///
+    /// Arguments
+    /// * file - The OPML file imported
+    pub fn summary(&self, file: &Path) -> String {
+        let mut summary = format!("Imported {} new sources from {}", self.added, file.display());
+        if !self.skipped.is_empty() {
+            summary.push_str(&format!(", skipped {}: {}", self.skipped.len(), self.skipped.join("; ")));
+        }
+        summary
+    }
 }
 
 /// Struct to store configuration we get from config file
 #[derive(Debug, Deserialize, Serialize)]
 struct Config {
     theme: String,
+    /// Themes defined by the user, which `theme` can pick alongside the built in ones
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    themes: BTreeMap<String, ThemeConfig>,
+    /// Keys for actions, replacing their default keys
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    keys: BTreeMap<String, KeyList>,
+    #[serde(default)]
+    sort: SortMode,
+    #[serde(default)]
+    fetch: FetchPolicy,
     sources: Vec<DataSources>,
 }
 
@@ -27,74 +71,50 @@ impl Default for Config {
     /// Use default theme and some standard sources
     fn default() -> Self {
         // Default sources
-        let cbc = DataSources {
-            name: "cbc".to_string(),
-            url: "https://www.cbc.ca/cmlink/rss-topstories".to_string(),
-        };
-        let cnn = DataSources {
-            name: "cnn".to_string(),
-            url: "http://rss.cnn.com/rss/cnn_topstories.rss".to_string(),
-        };
-        let globe: DataSources = DataSources {
-            name: "globe and mail".to_string(),
-            url: "https://www.theglobeandmail.com/arc/outboundfeeds/rss/category/canada/"
-                .to_string(),
-        };
+        let cbc = DataSources::new("cbc", "https://www.cbc.ca/cmlink/rss-topstories");
+        let cnn = DataSources::new("cnn", "http://rss.cnn.com/rss/cnn_topstories.rss");
+        let globe: DataSources = DataSources::new("globe and mail", "https://www.theglobeandmail.com/arc/outboundfeeds/rss/category/canada/");
         let sources = vec![cbc, cnn, globe];
 
         // Default theme
         let theme = "default".to_string();
         
-        Self { theme, sources }
+        Self { theme, themes: BTreeMap::new(), keys: BTreeMap::new(), sort: SortMode::default(), fetch: FetchPolicy::default(), sources }
     }
 }
 
 impl Config {
     /// Writes the settings to a file
-    fn write_config_to_file(&self) -> Result<(), Box<dyn Error>> {
-        let file = Config::config_path()?;
-        let a = file.clone();
-        let parent = a.strip_suffix("newsroom.toml").unwrap();
-        fs::create_dir_all(parent)?;
+    ///
+    /// Arguments
+    /// * file - The config file location
+    fn write_config_to_file(&self, file: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
         Ok(fs::write(file, toml::to_string(self).unwrap())?)
     }
 
     /// Loads the config file to a string
     /// 
     /// Arguments
-    /// * file - A string representing the config file location with respect to the file root
-    fn config_to_toml(file: &String) -> Result<Config, Box<dyn Error>>{
+    /// * file - The config file location with respect to the file root
+    fn config_to_toml(file: &Path) -> Result<Config, Box<dyn Error>>{
         // Try to load file into a TOML table
         Ok(toml::from_str(&fs::read_to_string(file)?)?)
     }
 
-    /// Returns a string representing the path to the .newsroom.toml file for the particular system
-    /// On linux this leads to ~/.newsroom.toml
-    /// On Windows this leads to ~/.newstoom.toml
-    /// On MacOS this leads to ~/.newstoom.toml
-    fn config_path() -> Result<String, Box<dyn Error>> {
+    /// Returns the path to the newsroom.toml file for the particular system
+    /// On linux this leads to ~/.Newsroom/newsroom.toml
+    /// On Windows this leads to ~/.Newsroom/config/newsroom.toml
+    /// On MacOS this leads to ~/.Newsroom/newsroom.toml
+    fn config_path() -> Result<PathBuf, Box<dyn Error>> {
         match env::consts::OS{
-            "linux" => {
-                // Get user home directory
-                let userdir = env::var("HOME")?;
-                // Merge home and target
-                let complete_path: String = userdir + r"\.Newsroom\newsroom.toml";
-                Ok(complete_path)
-            },
-            "macos" => {
-                // Get user home directory
-                let userdir = env::var("HOME")?;
-                // Merge home and target
-                let complete_path: String = userdir + r"\.Newsroom\newsroom.toml";
-                Ok(complete_path)
-            },
+            "linux" | "macos" => Ok(newsroom_dir()?.join("newsroom.toml")),
             "windows" => {
                 info!("Running in Windows env");
-                // Get user home directory
-                let userdir = env::var("USERPROFILE")?;
-                // Merge home and target
-                let complete_path: String = userdir + r"\.Newsroom\config\newsroom.toml";
-                info!("Using config path: {}", complete_path);
+                let complete_path = newsroom_dir()?.join("config").join("newsroom.toml");
+                info!("Using config path: {}", complete_path.display());
                 Ok(complete_path)
             },
             _ => Err("Unimplemented OS".into())
@@ -102,8 +122,49 @@ impl Config {
         }
     }
 
-    fn load_config() -> Result<Config, Box<dyn Error>> {
-        Config::config_to_toml(&Config::config_path()?)
+    /// Returns where versions before ~/.Newsroom/newsroom.toml kept the config file on linux and MacOS.
+    /// Its path was joined with backslashes, so the file sits beside the home directory with them in its name
+    fn legacy_config_path() -> Option<PathBuf> {
+        match env::consts::OS {
+            "linux" | "macos" => home_dir().ok().map(|home| PathBuf::from(home + r"\.Newsroom\newsroom.toml")),
+            _ => None,
+        }
+    }
+
+}
+
+/// Returns the user's home directory, read from HOME, or USERPROFILE on Windows
+fn home_dir() -> Result<String, Box<dyn Error>> {
+    match env::consts::OS {
+        "linux" | "macos" => Ok(env::var("HOME")?),
+        "windows" => Ok(env::var("USERPROFILE")?),
+        _ => Err("Unimplemented OS".into()),
+    }
+}
+
+/// Returns the directory newsroom keeps its files in, ~/.Newsroom on every system
+fn newsroom_dir() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(PathBuf::from(home_dir()?).join(".Newsroom"))
+}
+
+/// Returns the directory newsroom caches fetched data in, ~/.Newsroom/cache
+pub fn data_dir() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(newsroom_dir()?.join("cache"))
+}
+
+/// Returns where sources are exported to and imported from by default, ~/.Newsroom/newsroom.opml
+pub fn opml_path() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(newsroom_dir()?.join("newsroom.opml"))
+}
+
+/// Expands a leading "~" in a path typed by the user to their home directory
+///
+/// Arguments
+/// * path - The path as typed
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), home_dir()) {
+        (Some(rest), Ok(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => PathBuf::from(format!("{}{}", home, rest)),
+        _ => PathBuf::from(path),
     }
 }
 
@@ -112,88 +173,367 @@ impl Settings{
     /// First checks the system for a .newsroom.toml file 
     /// If a .newsroom.toml file is found, we read the file and copy the settings into it
     /// If a .newsroom.toml file is NOT found, we create the file with defaults
+    // Not Default, since it reads and may write newsroom.toml
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Settings {
         info!("Creating settings");
+        Settings::load(Config::config_path().ok(), Config::legacy_config_path())
+    }
+
+    /// Settings held only in memory with the default theme and keys, so tests don't depend on
+    /// the newsroom.toml of whoever runs them
+    ///
+    /// Arguments
+    /// * sources - The sources to hold
+    #[cfg(test)]
+    pub(crate) fn test(sources: Vec<DataSources>) -> Settings {
+        Settings {
+            theme: Theme::new(),
+            custom_themes: BTreeMap::new(),
+            keymap: Keymap::new(),
+            problems: vec![],
+            sources,
+            fetch: FetchPolicy::default(),
+            sort: SortMode::default(),
+            config_file: None,
+        }
+    }
 
-        let config = match Config::load_config() {
-            Ok(loaded_config) => {
-                // We've successfully loaded the config file
-                info!("Loaded the following config {:#?}", loaded_config);
-                loaded_config
+    /// Reads the settings from a newsroom.toml, creating it with defaults if it doesn't exist yet.
+    /// A file which can't be read is left alone for the user to fix, the defaults are used
+    /// in the meantime and the reason is added to the problems
+    ///
+    /// Arguments
+    /// * config_file - The newsroom.toml to read, None to use the defaults without saving them
+    /// * legacy_file - Where an older version kept newsroom.toml, copied to config_file if that doesn't exist yet
+    fn load(config_file: Option<PathBuf>, legacy_file: Option<PathBuf>) -> Settings {
+        let mut problems = vec![];
+        let config = match config_file.as_deref().map(|file| (file, fs::read_to_string(file))) {
+            Some((file, Ok(text))) => match toml::from_str::<Config>(&text) {
+                Ok(loaded_config) => {
+                    // We've successfully loaded the config file
+                    info!("Loaded the following config {:#?}", loaded_config);
+                    loaded_config
+                },
+                Err(e) => {
+                    problems.push(format!("Couldn't read {}, using the default settings until it is fixed: {}", file.display(), e));
+                    Config::default()
+                },
+            },
+            Some((file, Err(e))) if e.kind() == ErrorKind::NotFound && legacy_file.as_deref().is_some_and(Path::exists) => {
+                let legacy_file = legacy_file.unwrap_or_default();
+                info!("Copying the config file from {} to {}", legacy_file.display(), file.display());
+                let copied = file.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| fs::copy(&legacy_file, file));
+                match copied {
+                    Ok(_) => return Settings::load(config_file, None),
+                    Err(e) => {
+                        problems.push(format!("Couldn't copy {} to {}, using the default settings: {}", legacy_file.display(), file.display(), e));
+                        Config::default()
+                    },
+                }
             },
-            Err(e) => {
-                warn!("Couldn't load config file, error follows \n {}", e);
-                // Create a default config and save it to the file
+            Some((file, Err(e))) if e.kind() == ErrorKind::NotFound => {
+                info!("No config file at {}, creating one with the defaults", file.display());
                 let default_config = Config::default();
-                let res = default_config.write_config_to_file();
-                match res {
+                match default_config.write_config_to_file(file) {
                     Ok(_) => info!("File write successful"),
                     Err(e) => warn!("Config write failed \n {}", e),
                 };
                 default_config
             },
+            Some((file, Err(e))) => {
+                problems.push(format!("Couldn't read {}, using the default settings until it is fixed: {}", file.display(), e));
+                Config::default()
+            },
+            None => Config::default(),
         };
 
-        let theme = Theme::new();
-        Settings { theme, sources: config.sources }
+        let (keymap, keymap_problems) = Keymap::load(&config.keys);
+        problems.extend(keymap_problems);
+        let theme = Theme::load(&config.theme, &config.themes).unwrap_or_else(|e| {
+            problems.push(format!("Couldn't load the theme, using the default one: {}", e));
+            Theme::new()
+        });
+        for problem in &problems {
+            warn!("{}", problem);
+        }
+        Settings { theme, custom_themes: config.themes, keymap, problems, sources: config.sources, fetch: config.fetch, sort: config.sort, config_file }
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct Theme {
-    pub name: String,
-    pub root: Style,
-    pub content: Style,
-    pub app_title: Style,
-    pub tabs: Style,
-    pub tabs_selected: Style,
-    pub borders: Style,
-    pub description: Style,
-    pub description_title: Style,
-    pub keybinding: KeyBinding,
-}
+    /// Saves the settings back to newsroom.toml, keeping anything in the file the app can't change
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let Some(file) = &self.config_file else { return Ok(()) };
+        let mut config = Config::config_to_toml(file)?;
+        config.sources = self.sources.clone();
+        config.fetch = self.fetch.clone();
+        config.sort = self.sort;
+        config.write_config_to_file(file)
+    }
+
+    /// Saves the current theme as the one to use from now on
+    pub fn save_theme(&self) -> Result<(), Box<dyn Error>> {
+        let Some(file) = &self.config_file else { return Ok(()) };
+        let mut config = Config::config_to_toml(file)?;
+        config.theme = self.theme.name.clone();
+        config.write_config_to_file(file)
+    }
+
+    /// The names of every theme that can be picked, the built in ones first
+    pub fn theme_names(&self) -> Vec<String> {
+        let custom = self.custom_themes.keys().filter(|name| !BUILTIN_THEMES.contains(&name.as_str()));
+        BUILTIN_THEMES.iter().map(|name| name.to_string()).chain(custom.cloned()).collect()
+    }
+
+    /// The sources which haven't been disabled, in the order they are listed
+    pub fn enabled_sources(&self) -> Vec<DataSources> {
+        self.sources.iter().filter(|source| !source.disabled).cloned().collect()
+    }
+
+    /// Checks a source is valid and isn't already listed, without saving it
+    ///
+    /// Arguments
+    /// * source - The new or edited source
+    /// * replacing - The index of the source being edited, None for a new source
+    pub fn check_source(&self, source: &DataSources, replacing: Option<usize>) -> Result<(), Box<dyn Error>> {
+        source.validate()?;
+        let duplicate = self.sources
+            .iter()
+            .enumerate()
+            .any(|(index, existing)| Some(index) != replacing && existing.url == source.url);
+        match duplicate {
+            true => Err(format!("{} is already a source", source.url).into()),
+            false => Ok(()),
+        }
+    }
+
+    /// Adds a source, or replaces an existing one, after checking it is valid and isn't already listed, and saves the settings
+    ///
+    /// Arguments
+    /// * source - The new or edited source
+    /// * replacing - The index of the source being edited, None to add a new source
+    pub fn put_source(&mut self, source: DataSources, replacing: Option<usize>) -> Result<(), Box<dyn Error>> {
+        self.check_source(&source, replacing)?;
+        match replacing.and_then(|index| self.sources.get_mut(index)) {
+            Some(existing) => *existing = source,
+            None => self.sources.push(source),
+        }
+        self.save()
+    }
 
-impl Theme {
-    pub fn new() -> Theme{
-        Theme{
-            name: "Default".to_string(),
-            root: Style::new().bg(DARK_BLUE),
-            content: Style::new().bg(DARK_BLUE).fg(LIGHT_GRAY),
-            app_title: Style::new()
-                .fg(WHITE)
-                .bg(DARK_BLUE)
-                .add_modifier(Modifier::BOLD),
-            tabs: Style::new().fg(MID_GRAY).bg(DARK_BLUE),
-            tabs_selected: Style::new()
-                .fg(WHITE)
-                .bg(DARK_BLUE)
-                .add_modifier(Modifier::BOLD)
-                .add_modifier(Modifier::REVERSED),
-            borders: Style::new().fg(LIGHT_GRAY),
-            description: Style::new().fg(LIGHT_GRAY).bg(DARK_BLUE),
-            description_title: Style::new().fg(LIGHT_GRAY).add_modifier(Modifier::BOLD),
-            keybinding: KeyBinding {
-                key: Style::new().fg(BLACK).bg(DARK_GRAY),
-                description: Style::new().fg(DARK_GRAY).bg(BLACK),
+    /// Adds the sources listed in an OPML file, skipping any we already have or which aren't valid sources,
+    /// and saves the settings. Entries are checked the same way as a source added by hand
+    ///
+    /// Arguments
+    /// * file - The OPML file to read
+    pub fn import_opml(&mut self, file: &Path) -> Result<OpmlImport, Box<dyn Error>> {
+        let imported = read_opml(&fs::read_to_string(file)?)?;
+        let mut result = OpmlImport { added: 0, skipped: vec![] };
+        for source in imported {
+            if self.sources.iter().any(|existing| existing.url == source.url) {
+                continue;
             }
+            match source.validate() {
+                Ok(()) => {
+                    self.sources.push(source);
+                    result.added += 1;
+                },
+                Err(e) => result.skipped.push(format!("{}: {}", source.url, e)),
+            }
+        }
+        if result.added > 0 {
+            self.save()?;
         }
+        Ok(result)
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct KeyBinding {
-    pub key: Style,
-    pub description: Style,
+    /// Writes the sources to an OPML file, for importing into another reader
+    ///
+    /// Arguments
+    /// * file - The OPML file to write
+    pub fn export_opml(&self, file: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(parent) = file.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(fs::write(file, write_opml(&self.sources))?)
+    }
 }
 
-const DARK_BLUE: Color = Color::Rgb(16, 24, 48);
-// const LIGHT_BLUE: Color = Color::Rgb(64, 96, 192);
-// const LIGHT_YELLOW: Color = Color::Rgb(192, 192, 96);
-// const LIGHT_GREEN: Color = Color::Rgb(64, 192, 96);
-// const LIGHT_RED: Color = Color::Rgb(192, 96, 96);
-// const RED: Color = Color::Indexed(160);
-const BLACK: Color = Color::Indexed(232); // not really black, often #080808
-const DARK_GRAY: Color = Color::Indexed(238);
-const MID_GRAY: Color = Color::Indexed(244);
-const LIGHT_GRAY: Color = Color::Indexed(250);
-const WHITE: Color = Color::Indexed(255); // not really white, often #eeeeee
\ No newline at end of file
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::newsroomcore::storage::TempPath;
+
+    // Test that fetch settings and per source overrides are read from the config, and are optional
+    #[test]
+    fn test_fetch_config(){
+        let config: Config = toml::from_str(r#"
+            theme = "default"
+            sort = "source"
+
+            [fetch]
+            timeout_secs = 5
+            deadline_secs = 20
+
+            [[sources]]
+            name = "slow"
+            url = "https://example.com/slow.rss"
+            timeout_secs = 30
+            retries = 0
+            full_text = true
+
+            [[sources]]
+            name = "normal"
+            url = "https://example.com/normal.rss"
+        "#).unwrap();
+        assert_eq!(config.fetch, FetchPolicy { timeout_secs: 5, deadline_secs: 20, ..FetchPolicy::default() });
+        assert_eq!(config.fetch.for_source(&config.sources[0]).timeout_secs, 30);
+        assert_eq!(config.fetch.for_source(&config.sources[0]).retries, 0);
+        assert_eq!(config.fetch.for_source(&config.sources[1]), config.fetch);
+        assert!(config.sources[0].full_text && !config.sources[1].full_text);
+        // "source" is what the grouped mode used to be called
+        assert_eq!(config.sort, SortMode::Grouped);
+
+        let config: Config = toml::from_str(r#"
+            theme = "default"
+            sources = []
+
+            [keys]
+            quit = "ctrl-q"
+            refresh = ["r", "f5"]
+        "#).unwrap();
+        assert_eq!(config.fetch, FetchPolicy::default());
+        assert_eq!(config.sort, SortMode::Newest);
+        assert_eq!(config.keys["refresh"], KeyList::Many(vec!["r".to_string(), "f5".to_string()]));
+    }
+
+    // Test that a missing newsroom.toml is created, and that one which can't be read is left for the user to fix
+    #[test]
+    fn test_load_settings(){
+        let file = TempPath::new("load.toml");
+        let settings = Settings::load(Some(file.to_path_buf()), None);
+        assert!(settings.problems.is_empty());
+        assert_eq!(Config::config_to_toml(&file).unwrap().sources, Config::default().sources);
+
+        // A typo in a theme or a key shouldn't cost the user their sources
+        for broken in ["theme = \"mine\"\nsources = []\n\n[themes.mine]\nbase = \"light\"\ncontnet = { fg = 1 }\n", "theme = \"default\"\nsources = []\n\n[keys]\nquit = 1\n"] {
+            fs::write(&file, broken).unwrap();
+            let settings = Settings::load(Some(file.to_path_buf()), None);
+            let kept = fs::read_to_string(&file).unwrap();
+            assert_eq!(kept, broken);
+            assert_eq!(settings.sources, Config::default().sources);
+            assert_eq!(settings.problems.len(), 1);
+            assert!(settings.problems[0].starts_with("Couldn't read "));
+        }
+    }
+
+    // Test that the config file kept where older versions put it is copied over rather than replaced with defaults
+    #[test]
+    fn test_load_legacy_settings(){
+        let dir = TempPath::new("legacy");
+        let legacy = TempPath::new("legacy.toml");
+        let file = dir.join("newsroom.toml");
+        let text = "theme = \"light\"\n\n[[sources]]\nname = \"mine\"\nurl = \"https://example.com/feed.xml\"\n";
+        fs::write(&legacy, text).unwrap();
+        let settings = Settings::load(Some(file.clone()), Some(legacy.to_path_buf()));
+        let copied = fs::read_to_string(&file).unwrap();
+        assert!(settings.problems.is_empty());
+        assert_eq!(settings.theme.name, "light");
+        assert_eq!(settings.sources, vec![DataSources::new("mine", "https://example.com/feed.xml")]);
+        assert_eq!(copied, text);
+    }
+
+    // Test that the default config survives being written and read back
+    #[test]
+    fn test_default_config_roundtrip(){
+        let written = toml::to_string(&Config::default()).unwrap();
+        let config: Config = toml::from_str(&written).unwrap();
+        assert_eq!(config.fetch, FetchPolicy::default());
+        assert_eq!(config.sources, Config::default().sources);
+    }
+
+    // Test that saving settings rewrites the fields the app changes and keeps the rest of the file
+    #[test]
+    fn test_save_settings(){
+        let file = TempPath::new("config.toml");
+        fs::write(&file, "theme = \"custom\"\nsources = []\n\n[themes.custom]\nbase = \"light\"\ncontent = { fg = 236, bg = \"#ffffff\" }\n").unwrap();
+        let mut settings = Settings {
+            theme: Theme::new(),
+            custom_themes: BTreeMap::new(),
+            keymap: Keymap::new(),
+            problems: vec![],
+            sources: Config::default().sources,
+            fetch: FetchPolicy::default(),
+            sort: SortMode::RoundRobin,
+            config_file: Some(file.to_path_buf()),
+        };
+        settings.save().unwrap();
+
+        let config = Config::config_to_toml(&file).unwrap();
+        assert_eq!(config.theme, "custom");
+        assert_eq!(config.themes["custom"].base.as_deref(), Some("light"));
+        assert!(Theme::load(&config.theme, &config.themes).is_ok());
+        assert_eq!(config.sort, SortMode::RoundRobin);
+        assert_eq!(config.sources, settings.sources);
+
+        // The theme is only written when it is picked
+        settings.custom_themes = config.themes;
+        settings.theme = Theme::load("custom", &settings.custom_themes).unwrap();
+        assert_eq!(settings.theme_names().last().map(|name| name.as_str()), Some("custom"));
+        settings.theme = Theme::builtin("solarized").unwrap();
+        settings.save_theme().unwrap();
+        let config = Config::config_to_toml(&file).unwrap();
+        assert_eq!(config.theme, "solarized");
+        assert_eq!(config.sources, settings.sources);
+
+        // A file broken since it was read is left alone rather than replaced with the defaults
+        fs::write(&file, "theme = ").unwrap();
+        assert!(settings.save_theme().is_err());
+        assert!(settings.save().is_err());
+        let kept = fs::read_to_string(&file).unwrap();
+        assert_eq!(kept, "theme = ");
+    }
+
+    // Test that importing skips sources we already have and keeps the categories
+    #[test]
+    fn test_import_opml(){
+        let file = TempPath::new("import.opml");
+        let mut existing = DataSources::new("world", "https://example.com/world.rss");
+        existing.category = Some("Mine".to_string());
+        let mut settings = Settings {
+            theme: Theme::new(),
+            custom_themes: BTreeMap::new(),
+            keymap: Keymap::new(),
+            problems: vec![],
+            sources: vec![existing],
+            fetch: FetchPolicy::default(),
+            sort: SortMode::default(),
+            config_file: None,
+        };
+        fs::write(&file, include_str!("../resources/fixtures/subscriptions.opml")).unwrap();
+        assert_eq!(settings.import_opml(&file).unwrap(), OpmlImport { added: 4, skipped: vec![] });
+        assert_eq!(settings.import_opml(&file).unwrap().added, 0);
+        assert_eq!(settings.sources[0].category.as_deref(), Some("Mine"));
+        assert_eq!(settings.sources[1].category.as_deref(), Some("Tech/Linux"));
+
+        settings.export_opml(&file).unwrap();
+        let exported = read_opml(&fs::read_to_string(&file).unwrap()).unwrap();
+        assert_eq!(exported, settings.sources);
+
+        // Entries which couldn't be added by hand are left out and reported. A blank xmlUrl is read as a folder, not a feed
+        fs::write(&file, r#"<opml version="2.0"><body>
+            <outline text="Script" xmlUrl="javascript:alert(1)"/>
+            <outline text="Relative" xmlUrl="feeds/local.xml"/>
+            <outline text="Blank" xmlUrl=""/>
+            <outline text="Fine" xmlUrl="https://example.com/fine.rss"/>
+        </body></opml>"#).unwrap();
+        let sources = settings.sources.len();
+        let result = settings.import_opml(&file).unwrap();
+        assert_eq!(result.added, 1);
+        assert_eq!(result.skipped, vec![
+            "javascript:alert(1): The URL must start with http:// or https://",
+            "feeds/local.xml: The URL isn't valid: relative URL without a base",
+        ]);
+        assert_eq!(settings.sources.len(), sources + 1);
+        assert!(result.summary(Path::new("feeds.opml")).starts_with("Imported 1 new sources from feeds.opml, skipped 2: javascript:"));
+    }
+}
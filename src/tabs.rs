@@ -1,5 +1,13 @@
+pub mod form;
+pub mod help;
 pub mod news;
+pub mod reader;
+pub mod saved;
 pub mod settings;
 
-pub use crate::tabs::settings::SettingsTab;
-pub use crate::tabs::news::NewsTab;
\ No newline at end of file
+pub use crate::tabs::settings::{SettingsTab, ThemePicker};
+pub use crate::tabs::news::NewsTab;
+pub use crate::tabs::saved::SavedTab;
+pub use crate::tabs::reader::{FullText, Reader, ReaderView};
+pub use crate::tabs::form::{Form, FormView};
+pub use crate::tabs::help::{Help, HelpView};
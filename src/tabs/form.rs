@@ -0,0 +1,197 @@
+use tui::{
+    prelude::*,
+    widgets::*,
+};
+
+use itertools::Itertools;
+
+use crate::{keymap::{Action, Keymap}, newsroomcore::formaction::FormAction, settings::Theme};
+
+/// A labelled text input
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormField {
+    pub label: String,
+    pub value: String,
+}
+
+/// A popup of text inputs, filled in one at a time and submitted together,
+/// or a list of options to pick one from
+#[derive(Debug, Clone)]
+pub struct Form {
+    pub action: FormAction,
+    pub title: String,
+    pub fields: Vec<FormField>,
+    /// The choices offered instead of fields
+    pub options: Vec<String>,
+    /// The field being typed into, or the option picked
+    pub focus: usize,
+    /// Why the last submission was rejected, shown until the form is submitted again
+    pub error: Option<String>,
+    /// The url being checked before the form is carried out, while the form waits for it
+    pub checking: Option<String>,
+}
+
+impl Form {
+    /// Creates a form with its fields filled in with starting values
+    ///
+    /// Arguments
+    /// * action - What submitting the form does
+    /// * title - The title of the popup
+    /// * fields - The label and starting value of each field
+    pub fn new(action: FormAction, title: &str, fields: Vec<(&str, String)>) -> Form {
+        Form {
+            action,
+            title: title.to_string(),
+            fields: fields.into_iter().map(|(label, value)| FormField { label: label.to_string(), value }).collect(),
+            options: vec![],
+            focus: 0,
+            error: None,
+            checking: None,
+        }
+    }
+
+    /// Creates a form which asks to pick one of a list of options
+    ///
+    /// Arguments
+    /// * action - What submitting the form does
+    /// * title - The title of the popup
+    /// * options - The text of each option
+    pub fn choice(action: FormAction, title: &str, options: Vec<String>) -> Form {
+        Form { options, ..Form::new(action, title, vec![]) }
+    }
+
+    /// The value of a field, without surrounding whitespace
+    pub fn value(&self, field: usize) -> &str {
+        self.fields.get(field).map_or("", |field| field.value.trim())
+    }
+
+    pub fn input(&mut self, c: char) {
+        if let Some(field) = self.fields.get_mut(self.focus) {
+            field.value.push(c);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(field) = self.fields.get_mut(self.focus) {
+            field.value.pop();
+        }
+    }
+
+    /// Replaces the text of the focused field
+    pub fn set(&mut self, value: &str) {
+        if let Some(field) = self.fields.get_mut(self.focus) {
+            field.value = value.to_string();
+        }
+    }
+
+    /// Moves to the next field or option, wrapping around to the first
+    pub fn next_field(&mut self) {
+        self.focus = (self.focus + 1) % self.stops();
+    }
+
+    /// Moves to the previous field or option, wrapping around to the last
+    pub fn previous_field(&mut self) {
+        let count = self.stops();
+        self.focus = (self.focus + count - 1) % count;
+    }
+
+    /// How many places the focus can move between
+    fn stops(&self) -> usize {
+        self.fields.len().max(self.options.len()).max(1)
+    }
+}
+
+/// Shows a form as a popup in the middle of the screen
+pub struct FormView<'a> {
+    form: &'a Form,
+    theme: Theme,
+    /// How to confirm or cancel the form, like "↵ to confirm, Esc to cancel"
+    hint: String,
+}
+
+impl FormView<'_> {
+    pub fn new<'a>(form: &'a Form, theme: &Theme, keymap: &Keymap) -> FormView<'a> {
+        let hint = [(Action::SubmitForm, "confirm"), (Action::CancelForm, "cancel")]
+            .iter()
+            .filter_map(|(action, desc)| keymap.label(*action).map(|label| format!("{} to {}", label, desc)))
+            .join(", ");
+        FormView { form, theme: theme.clone(), hint }
+    }
+}
+
+impl Widget for FormView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Each field takes a bordered box of 3 rows and each option a row, plus a row for the error and the form's own borders
+        let height = (self.form.fields.len() as u16 * 3 + self.form.options.len() as u16 + 3).min(area.height);
+        let width = area.width.saturating_sub(4).clamp(20.min(area.width), 72);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        Clear.render(popup, buf);
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(format!(" {} ", self.form.title))
+            .style(self.theme.content);
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+
+        let mut constraints = vec![Constraint::Length(3); self.form.fields.len()];
+        constraints.extend(vec![Constraint::Length(1); self.form.options.len()]);
+        constraints.push(Constraint::Length(1));
+        let rows = Layout::new().direction(Direction::Vertical).constraints(constraints).split(inner);
+        for (index, field) in self.form.fields.iter().enumerate() {
+            let focused = index == self.form.focus;
+            let cursor = if focused { "█" } else { "" };
+            // Keep the end of long values, where the typing happens, in view
+            let visible = rows[index].width.saturating_sub(3) as usize;
+            let value: String = field.value.chars().rev().take(visible).collect::<Vec<_>>().into_iter().rev().collect();
+            let border = if focused { self.theme.description_title } else { self.theme.description };
+            Paragraph::new(format!("{}{}", value, cursor))
+                .style(self.theme.content)
+                .block(Block::new().borders(Borders::ALL).border_style(border).title(field.label.as_str()))
+                .render(rows[index], buf);
+        }
+        for (index, option) in self.form.options.iter().enumerate() {
+            let line = match index == self.form.focus {
+                true => Line::styled(format!("▸ {}", option), self.theme.description_title),
+                false => Line::styled(format!("  {}", option), self.theme.content),
+            };
+            Paragraph::new(line).render(rows[self.form.fields.len() + index], buf);
+        }
+        let status = match (&self.form.checking, &self.form.error) {
+            (Some(url), _) => Line::styled(format!("Checking {}…", url), self.theme.description),
+            (None, Some(error)) => Line::styled(error.clone(), self.theme.error),
+            (None, None) => Line::styled(self.hint, self.theme.description),
+        };
+        Paragraph::new(status).render(rows[self.form.fields.len() + self.form.options.len()], buf);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_form_editing(){
+        let mut form = Form::new(FormAction::ImportOpml, "Import", vec![("File", "feeds".to_string()), ("Other", String::new())]);
+        form.input('.');
+        form.backspace();
+        form.input('!');
+        form.previous_field();
+        form.input(' ');
+        assert_eq!(form.fields[0].value, "feeds!");
+        assert_eq!(form.value(1), "");
+        form.next_field();
+        assert_eq!(form.focus, 0);
+
+        let mut choice = Form::choice(FormAction::AddSource, "Pick", vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        choice.previous_field();
+        assert_eq!(choice.focus, 2);
+        choice.input('x');
+        assert!(choice.fields.is_empty());
+    }
+}
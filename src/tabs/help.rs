@@ -0,0 +1,141 @@
+use tui::{
+    prelude::*,
+    widgets::*,
+};
+
+use crate::{command::COMMANDS, keymap::{Action, Keymap, Scope}, settings::Theme};
+
+/// Rows taken up outside the help's text: the app's title and bottom bars, a margin above and below the popup, and its borders
+const CHROME_HEIGHT: u16 = 6;
+
+/// Every action and the keys bound to it, grouped by where the keys work, followed by the commands
+#[derive(Debug, Clone)]
+pub struct Help {
+    pub text: Vec<Line<'static>>,
+    /// How many rows the text is scrolled down by
+    pub scroll: usize,
+}
+
+impl Help {
+    /// Lists the keymap's actions under a heading for each scope, then every command
+    ///
+    /// Arguments
+    /// * keymap - The keys in use
+    /// * theme - The theme to style the text with
+    pub fn new(keymap: &Keymap, theme: &Theme) -> Help {
+        let labels: Vec<(Action, Option<String>)> = Action::ALL.into_iter().map(|action| (action, keymap.label(action))).collect();
+        let width = labels.iter().filter_map(|(_, label)| label.as_ref()).map(|label| label.chars().count()).max().unwrap_or(0);
+        let mut text = vec![];
+        for scope in Scope::ALL {
+            if !text.is_empty() {
+                text.push(Line::default());
+            }
+            text.push(Line::styled(scope.title(), theme.description_title));
+            for (action, label) in labels.iter().filter(|(action, _)| action.scope() == scope) {
+                let line = match label {
+                    Some(label) => Line::from(vec![
+                        Span::styled(format!(" {} ", label), theme.keybinding.key),
+                        Span::raw(" ".repeat(width - label.chars().count() + 1)),
+                        Span::styled(action.description(), theme.content),
+                    ]),
+                    None => Line::from(vec![
+                        Span::styled(format!("{:<1$}", " unbound", width + 3), theme.description.add_modifier(Modifier::DIM)),
+                        Span::styled(action.description(), theme.content),
+                    ]),
+                };
+                text.push(line);
+            }
+        }
+        text.push(Line::default());
+        text.push(Line::styled("Commands", theme.description_title));
+        let width = COMMANDS.iter().map(|(_, usage, _)| usage.chars().count()).max().unwrap_or(0);
+        for (_, usage, description) in COMMANDS {
+            text.push(Line::from(vec![
+                Span::styled(format!(" :{:<1$} ", usage, width), theme.keybinding.key),
+                Span::raw(" "),
+                Span::styled(description, theme.content),
+            ]));
+        }
+        Help { text, scroll: 0 }
+    }
+
+    /// Scrolls by a number of rows, negative to scroll up, without running past either end of the text
+    ///
+    /// Arguments
+    /// * rows - How far to scroll
+    /// * viewport - The width and height of the terminal
+    pub fn scroll_by(&mut self, rows: i32, viewport: (u16, u16)) {
+        let max_scroll = self.text.len().saturating_sub(Help::text_height(viewport) as usize);
+        self.scroll = (self.scroll as i64 + rows as i64).clamp(0, max_scroll as i64) as usize;
+    }
+
+    /// How many rows of the help fit on a terminal of the given size
+    ///
+    /// Arguments
+    /// * viewport - The width and height of the terminal
+    pub fn text_height(viewport: (u16, u16)) -> u16 {
+        viewport.1.saturating_sub(CHROME_HEIGHT)
+    }
+}
+
+/// Shows the help as a popup in the middle of the screen
+pub struct HelpView<'a> {
+    help: &'a Help,
+    theme: Theme,
+}
+
+impl HelpView<'_> {
+    pub fn new<'a>(help: &'a Help, theme: &Theme) -> HelpView<'a> {
+        HelpView { help, theme: theme.clone() }
+    }
+}
+
+impl Widget for HelpView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let width = area.width.saturating_sub(4).clamp(20.min(area.width), 72);
+        let height = area.height.saturating_sub(2);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        Clear.render(popup, buf);
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .padding(Padding::horizontal(1))
+            .title(" Keys ")
+            .style(self.theme.content);
+        Paragraph::new(Text::from(self.help.text.clone()))
+            .scroll((self.help.scroll as u16, 0))
+            .block(block)
+            .render(popup, buf);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_help_lists_every_action(){
+        let (keymap, _) = Keymap::load(&toml::from_str("open_in_browser = []").unwrap());
+        let mut help = Help::new(&keymap, &Theme::new());
+        let rows: Vec<String> = help.text
+            .iter()
+            .map(|line| line.spans.iter().map(|span| span.content.as_ref()).collect())
+            .collect();
+        // A heading for each scope and for the commands, with a blank line between them
+        assert_eq!(rows.len(), Action::ALL.len() + COMMANDS.len() + (Scope::ALL.len() + 1) * 2 - 1);
+        assert_eq!(rows[0], "Everywhere");
+        assert!(rows[1].starts_with(" q/Ctrl-c ") && rows[1].ends_with(" Quit newsroom"));
+        assert!(rows.iter().any(|row| row.starts_with(" unbound") && row.ends_with("Open the article in the browser")));
+        assert!(rows.contains(&"Search".to_string()));
+        assert!(rows.last().is_some_and(|row| row.starts_with(" :quit ") && row.ends_with(" Quit newsroom")));
+
+        let viewport = (80, 10 + CHROME_HEIGHT);
+        help.scroll_by(i32::MAX, viewport);
+        assert_eq!(help.scroll, rows.len() - 10);
+    }
+}
@@ -1,16 +1,27 @@
-use crate::{newsroomcore::{newsroomstate::NewsroomState, newsarticle::NewsArticle}, settings::Theme};
+use crate::{app::App, newsroomcore::{newsroomstate::NewsroomState, newsarticle::NewsArticle, datasources::DataSources, fetchstatus::SourceStatus, readarticles::ReadArticles, savedarticles::SavedArticles, sortmode::SortMode, listfilter::{ListFilter, match_ranges}}, richtext::RichText, settings::Theme};
 use itertools::Itertools;
-use log::info;
 use tui::{
     prelude::*,
     widgets::*,
 };
+use chrono::{DateTime, FixedOffset, Utc};
 use textwrap::{wrap, Options};
+use throbber_widgets_tui::{Throbber, ThrobberState};
 
 pub struct NewsTab<'a>{
     nrs: &'a NewsroomState,
     list_state: ListState,
     theme: Theme,
+    fetch_status: &'a [(DataSources, SourceStatus)],
+    throbber_state: ThrobberState,
+    read: &'a ReadArticles,
+    saved: &'a SavedArticles,
+    sort: SortMode,
+    filter: &'a ListFilter,
+    sources: Vec<DataSources>,
+    sidebar_open: bool,
+    sidebar_focused: bool,
+    sidebar_row: usize,
 }
 
 impl NewsTab <'_>{
@@ -19,7 +30,7 @@ impl NewsTab <'_>{
     /// Arguments
     /// * text - A string representing the text to be wrapped
     /// * width - The line width to wrap to
-    fn wrap_text(text_raw: String, width: usize) -> String {
+    pub(crate) fn wrap_text(text_raw: String, width: usize) -> String {
         let options = Options::new(width);
 
         // let text_raw = format!("{}: {}", x.source.name, x.title);
@@ -29,39 +40,317 @@ impl NewsTab <'_>{
         text_wrapped.join("\n")
     }
 
-    pub fn new(nrs: &NewsroomState, theme: Theme, index: Option<usize>) -> NewsTab {
+    pub fn new(context: &App) -> NewsTab<'_> {
         let mut list_state = ListState::default();
-        list_state.select(index);
-        NewsTab { nrs, list_state, theme}
+        list_state.select(context.row);
+        NewsTab {
+            nrs: &context.newsroom_state,
+            list_state,
+            theme: context.settings.theme.clone(),
+            fetch_status: &context.fetch_status,
+            throbber_state: context.throbber_state.clone(),
+            read: &context.read,
+            saved: &context.saved,
+            sort: context.settings.sort,
+            filter: &context.filter,
+            sources: context.settings.enabled_sources(),
+            sidebar_open: context.sidebar_open,
+            sidebar_focused: context.sidebar_focused,
+            sidebar_row: context.sidebar_row,
+        }
+    }
+
+    /// Describes how long ago an article was published, e.g. "3h ago", falling back to the date for old articles
+    ///
+    /// Arguments
+    /// * published - When the article was published
+    /// * now - The current time
+    pub(crate) fn relative_age(published: DateTime<FixedOffset>, now: DateTime<Utc>) -> String {
+        let age = now.signed_duration_since(published);
+        match age {
+            _ if age.num_minutes() < 1 => "just now".to_string(),
+            _ if age.num_hours() < 1 => format!("{}m ago", age.num_minutes()),
+            _ if age.num_days() < 1 => format!("{}h ago", age.num_hours()),
+            _ if age.num_days() < 30 => format!("{}d ago", age.num_days()),
+            _ => published.format("%Y-%m-%d").to_string(),
+        }
+    }
+
+    /// Counts the unread articles from each source, in alphabetical order of source name.
+    /// Sources are told apart by url, so two sharing a name get a count each
+    ///
+    /// Arguments
+    /// * articles - The articles being displayed
+    fn unread_counts(&self, articles: &[NewsArticle]) -> Vec<(String, usize)> {
+        let mut counts: Vec<(&DataSources, usize)> = vec![];
+        for article in articles {
+            let unread = usize::from(!self.read.is_read(&article.id));
+            match counts.iter_mut().find(|(source, _)| source.url == article.source.url) {
+                Some((_, count)) => *count += unread,
+                None => counts.push((&article.source, unread)),
+            }
+        }
+        counts.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+        counts.into_iter().map(|(source, count)| (source.name.clone(), count)).collect()
     }
 
     pub fn render_news_list(&mut self, area: Rect, buf: &mut Buffer, articles: Vec<NewsArticle>) {
-            let items: Vec<ListItem> = articles
-            .iter()
-            .map(|x| {
-                let text = Text::from(NewsTab::wrap_text(format!("{}: {}", x.source.name, x.title), area.width as usize));
-                ListItem::new(text)
+            let now = Utc::now();
+            let grouped = self.sort == SortMode::Grouped;
+            let mut items: Vec<ListItem> = vec![];
+            // Headers take up rows too, so the selected article sits further down the list than its index
+            let mut selected = self.list_state.selected();
+            for (index, x) in articles.iter().enumerate() {
+                let new_group = index == 0 || articles[index - 1].source.url != x.source.url;
+                if grouped && new_group {
+                    items.push(ListItem::new(Text::from(x.source.name.clone())).style(self.theme.description_title));
+                    selected = selected.map(|row| if row >= index { row + 1 } else { row });
+                }
+
+                let star = if self.saved.is_saved(&x.id) { "★ " } else { "" };
+                let age = match x.published {
+                    Some(published) => format!(" ({})", NewsTab::relative_age(published, now)),
+                    None => "".to_string(),
+                };
+                let line = match grouped {
+                    true => format!("  {}{}{}", star, x.title, age),
+                    false => format!("{}{}: {}{}", star, x.source.name, x.title, age),
+                };
+                let text = self.highlight_matches(NewsTab::wrap_text(line, area.width as usize));
+                // Unread articles stand out in bold
+                items.push(match self.read.is_read(&x.id) {
+                    true => ListItem::new(text),
+                    false => ListItem::new(text).add_modifier(Modifier::BOLD),
+                });
             }
-            )
-            .collect();
+            self.list_state.select(selected);
+
+            let counts = self.unread_counts(&articles)
+                .iter()
+                .map(|(name, count)| format!("{} {}", name, count))
+                .join(" · ");
+            let title = if counts.is_empty() { String::new() } else { format!(" Unread: {} ", counts) };
             let list_widget = List::new(items)
                 .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
-                .style(self.theme.content).block(Block::new().borders(Borders::ALL).border_type(BorderType::Rounded));
+                .style(self.theme.content).block(Block::new().borders(Borders::ALL).border_type(BorderType::Rounded).title(title));
 
             tui::widgets::StatefulWidget::render(list_widget, area, buf, &mut self.list_state); // Render widget
     }
 
+    /// Renders the source sidebar, an "All" entry followed by every source with its unread count
+    ///
+    /// Arguments
+    /// * articles - Every article being held, not just the ones passing the filter
+    pub fn render_sidebar(&self, area: Rect, buf: &mut Buffer, articles: &[NewsArticle]) {
+        let unread = |url: Option<&str>| articles
+            .iter()
+            .filter(|article| (url.is_none() || url == Some(article.source.url.as_str())) && !self.read.is_read(&article.id))
+            .count();
+        let mut items = vec![ListItem::new(format!("All ({})", unread(None)))];
+        items.extend(self.sources.iter().map(|source| {
+            ListItem::new(format!("{} ({})", source.name, unread(Some(&source.url))))
+        }));
+
+        // The chosen source stays marked while focus is on the news list
+        let highlight = match self.sidebar_focused {
+            true => Style::default().add_modifier(Modifier::REVERSED),
+            false => Style::default().add_modifier(Modifier::BOLD),
+        };
+        let list_widget = List::new(items)
+            .highlight_style(highlight)
+            .style(self.theme.content)
+            .block(Block::new().borders(Borders::ALL).border_type(BorderType::Rounded).title("Sources"));
+        let mut state = ListState::default();
+        state.select(Some(self.sidebar_row));
+        StatefulWidget::render(list_widget, area, buf, &mut state);
+    }
+
+    /// Highlights every place the search query appears in the text
+    ///
+    /// Arguments
+    /// * text - The text to highlight, possibly spanning several lines
+    fn highlight_matches(&self, text: String) -> Text<'static> {
+        let lines: Vec<Line> = text
+            .lines()
+            .map(|line| {
+                let chars: Vec<char> = line.chars().collect();
+                let mut spans = vec![];
+                let mut end_of_last = 0;
+                for (start, end) in match_ranges(line, &self.filter.query) {
+                    spans.push(Span::raw(chars[end_of_last..start].iter().collect::<String>()));
+                    spans.push(Span::styled(chars[start..end].iter().collect::<String>(), self.theme.highlight));
+                    end_of_last = end;
+                }
+                spans.push(Span::raw(chars[end_of_last..].iter().collect::<String>()));
+                Line::from(spans)
+            })
+            .collect();
+        Text::from(lines)
+    }
+
+    /// Renders the search query, with a cursor while it is being typed, and how many articles match
+    pub fn render_search_line(&self, area: Rect, buf: &mut Buffer, matches: usize) {
+        let cursor = if self.filter.editing { "█" } else { "" };
+        let line = Line::from(vec![
+            Span::styled(format!("/{}{}", self.filter.query, cursor), self.theme.content),
+            Span::styled(format!("  {} matches", matches), self.theme.description),
+        ]);
+        Paragraph::new(line).style(self.theme.content).render(area, buf);
+    }
+
     pub fn render_selected_description(&self, area: Rect, buf: &mut Buffer, article: NewsArticle){
-        let text = article.summary;
-        Paragraph::new(text).wrap(Wrap { trim: true }).style(self.theme.content).block(Block::new().borders(Borders::ALL).border_type(BorderType::Rounded).title("Description")).render(area, buf);
+        let text = RichText::from_html(&article.summary, &self.theme);
+        Paragraph::new(text.lines).wrap(Wrap { trim: false }).style(self.theme.content).block(Block::new().borders(Borders::ALL).border_type(BorderType::Rounded).title("Description")).render(area, buf);
+    }
+
+    /// Sources which failed to load during the last fetch along with the reason
+    fn failures(&self) -> Vec<String> {
+        self.fetch_status
+            .iter()
+            .filter_map(|(source, status)| match status {
+                SourceStatus::Failed { error, .. } => Some(format!("{}: {}", source.name, error)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Lists the sources which failed to load during the last fetch along with the reason
+    pub fn render_failures(&self, area: Rect, buf: &mut Buffer, failures: Vec<String>) {
+        let items: Vec<ListItem> = failures
+            .into_iter()
+            .map(ListItem::new)
+            .collect();
+        let list_widget = List::new(items)
+            .style(self.theme.error)
+            .block(Block::new().borders(Borders::ALL).border_type(BorderType::Rounded).title("Failed sources"));
+        tui::widgets::Widget::render(list_widget, area, buf);
+    }
+
+    /// Renders a single spinner line summarising the fetch, shown above the list while sources are still loading
+    pub fn render_fetch_progress(&mut self, area: Rect, buf: &mut Buffer) {
+        let finished = self.fetch_status.iter().filter(|(_, status)| status.is_finished()).count();
+        let label = format!("Loading news, {} of {} sources done", finished, self.fetch_status.len());
+        let throbber = Throbber::default()
+            .label(label)
+            .style(self.theme.description)
+            .throbber_style(self.theme.description_title);
+        StatefulWidget::render(throbber, area, buf, &mut self.throbber_state);
+    }
+
+    /// Renders one line per source while fetching, a spinner while it loads,
+    /// then a check or cross with the article count or error once it finishes
+    pub fn render_fetch_status(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = Block::new().borders(Borders::ALL).border_type(BorderType::Rounded).title("Loading news").style(self.theme.content);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        for (i, (source, status)) in self.fetch_status.iter().enumerate().take(inner.height as usize) {
+            let row = Rect::new(inner.x + 1, inner.y + i as u16, inner.width.saturating_sub(1), 1);
+            match status {
+                SourceStatus::Loading => {
+                    let throbber = Throbber::default()
+                        .label(source.name.as_str())
+                        .style(self.theme.content)
+                        .throbber_style(self.theme.description_title);
+                    StatefulWidget::render(throbber, row, buf, &mut self.throbber_state);
+                },
+                SourceStatus::Loaded { articles, elapsed } => {
+                    let line = Line::from(vec![
+                        Span::styled("✔ ", self.theme.success),
+                        Span::styled(source.name.as_str(), self.theme.content),
+                        Span::styled(format!("  {} articles in {:.1}s", articles, elapsed.as_secs_f32()), self.theme.description),
+                    ]);
+                    Paragraph::new(line).render(row, buf);
+                },
+                SourceStatus::Failed { error, elapsed } => {
+                    let line = Line::from(vec![
+                        Span::styled("✘ ", self.theme.error),
+                        Span::styled(source.name.as_str(), self.theme.content),
+                        Span::styled(format!("  {} after {:.1}s", error, elapsed.as_secs_f32()), self.theme.error),
+                    ]);
+                    Paragraph::new(line).render(row, buf);
+                },
+            }
+        }
     }
 }
 
 impl Widget for NewsTab <'_>{
     fn render(mut self, area: Rect, buf: &mut Buffer) {
+        // Reserve space at the bottom to report any sources which failed to load
+        let failures = self.failures();
+        let area = match (self.nrs, failures.len()) {
+            (NewsroomState::DisplayMedia(_), count) if count > 0 => {
+                let layout = Layout::new()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Min(0),
+                    Constraint::Length(count as u16 + 2),
+                ])
+                .split(area);
+                self.render_failures(layout[1], buf, failures);
+                layout[0]
+            },
+            _ => area,
+        };
+
+        // Keep showing progress above the list while the remaining sources load
+        let still_loading = self.fetch_status.iter().any(|(_, status)| !status.is_finished());
+        let area = match self.nrs {
+            NewsroomState::DisplayMedia(_) if still_loading => {
+                let layout = Layout::new()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(1),
+                    Constraint::Min(0),
+                ])
+                .split(area);
+                self.render_fetch_progress(layout[0], buf);
+                layout[1]
+            },
+            _ => area,
+        };
+
         match self.nrs {
             NewsroomState::DisplayMedia(articles) => {
-                match self.list_state.selected() {
+                let rows = self.filter.visible(articles);
+                let visible: Vec<NewsArticle> = rows.iter().map(|&row| articles[row].clone()).collect();
+
+                let area = match self.sidebar_open {
+                    true => {
+                        let layout = Layout::new()
+                        .direction(Direction::Horizontal)
+                        .constraints([
+                            Constraint::Length(28),
+                            Constraint::Min(0),
+                        ])
+                        .split(area);
+                        self.render_sidebar(layout[0], buf, articles);
+                        layout[1]
+                    },
+                    false => area,
+                };
+
+                // Reserve a line below the list for the search
+                let area = match self.filter.is_active() {
+                    true => {
+                        let layout = Layout::new()
+                        .direction(Direction::Vertical)
+                        .constraints([
+                            Constraint::Min(0),
+                            Constraint::Length(1),
+                        ])
+                        .split(area);
+                        self.render_search_line(layout[1], buf, visible.len());
+                        layout[0]
+                    },
+                    false => area,
+                };
+
+                // Only the visible articles are listed, so select the selected article's place among them
+                let selected = self.list_state.selected().and_then(|row| rows.iter().position(|&visible_row| visible_row == row));
+                self.list_state.select(selected);
+                match selected {
                     Some(index) => {
                         // The user has selected a row, display an extended description below the list
                         let layout = Layout::new()
@@ -73,17 +362,17 @@ impl Widget for NewsTab <'_>{
                         .split(area);
                         
                         // Render using layout
-                        self.render_news_list(layout[0], buf, articles.clone().to_vec());
-                        self.render_selected_description(layout[1], buf, articles[index].clone());
+                        self.render_selected_description(layout[1], buf, visible[index].clone());
+                        self.render_news_list(layout[0], buf, visible);
                     },
                     None => {
                         // The user has not selected a row yet, do not render an extended description
                         // Use the whole area for the list
-                        self.render_news_list(area, buf, articles.clone().to_vec());
+                        self.render_news_list(area, buf, visible);
                     },
                 }
             },
-            NewsroomState::FetchMedia(_) => Paragraph::new("Loading news . . .").style(self.theme.description).alignment(Alignment::Center).render(area, buf),
+            NewsroomState::FetchMedia(_) => self.render_fetch_status(area, buf),
             NewsroomState::Homescreen => Paragraph::new("Press 'l' to load news articles").alignment(Alignment::Center).render(area, buf),
             _ => {},
         }
@@ -108,4 +397,17 @@ mod test {
         println!("{}", result);
         assert_eq!(result.lines().collect_vec().len(), 1)
     }
-}
\ No newline at end of file
+
+    // Relative ages
+    #[test]
+    fn test_relative_age() {
+        let now = DateTime::parse_from_rfc3339("2023-10-03T12:00:00Z").unwrap().with_timezone(&Utc);
+        let age = |date: &str| NewsTab::relative_age(DateTime::parse_from_rfc3339(date).unwrap(), now);
+        assert_eq!(age("2023-10-03T11:59:30Z"), "just now");
+        assert_eq!(age("2023-10-03T11:15:00Z"), "45m ago");
+        assert_eq!(age("2023-10-03T08:00:00-03:00"), "1h ago");
+        assert_eq!(age("2023-10-03T09:00:00Z"), "3h ago");
+        assert_eq!(age("2023-09-30T12:00:00Z"), "3d ago");
+        assert_eq!(age("2023-06-01T12:00:00Z"), "2023-06-01");
+    }
+}
@@ -0,0 +1,155 @@
+use chrono::Utc;
+use tui::{
+    prelude::*,
+    widgets::*,
+};
+
+use crate::{newsroomcore::newsarticle::NewsArticle, richtext::RichText, settings::Theme, tabs::NewsTab};
+
+/// Rows taken up outside the reader's text: the app's title and bottom bars, and the reader's borders
+const CHROME_HEIGHT: u16 = 4;
+/// Columns taken up outside the reader's text: the borders and a column of padding either side
+const CHROME_WIDTH: u16 = 4;
+
+/// An article opened in the reader, with its body already rendered
+#[derive(Debug, Clone)]
+pub struct Reader {
+    pub article: NewsArticle,
+    /// The headline, byline and body, ready to render
+    pub text: RichText,
+    /// How many rows the text is scrolled down by
+    pub scroll: usize,
+    /// Where the full story from the article's page has got to
+    pub full_text: FullText,
+}
+
+/// Progress fetching the full story for sources which only carry a teaser
+#[derive(Debug, Clone, PartialEq)]
+pub enum FullText {
+    /// The source doesn't want the full story fetched
+    Skipped,
+    Loading,
+    Loaded,
+    Failed(String),
+}
+
+impl Reader {
+    /// Renders an article for reading, preferring its full content over the summary
+    ///
+    /// Arguments
+    /// * article - The article to read
+    /// * theme - The theme to style the text with
+    pub fn new(article: NewsArticle, theme: &Theme) -> Reader {
+        let body = article
+            .content
+            .as_deref()
+            .filter(|content| !content.trim().is_empty())
+            .unwrap_or(&article.summary);
+        let mut text = RichText::from_html(body, theme);
+
+        let mut byline = vec![article.source.name.clone()];
+        if let Some(published) = article.published {
+            byline.push(NewsTab::relative_age(published, Utc::now()));
+        }
+        let authors = article.authors.iter().filter(|author| !author.is_empty()).cloned().collect::<Vec<_>>();
+        if !authors.is_empty() {
+            byline.push(authors.join(", "));
+        }
+        let header = vec![
+            Line::styled(article.title.clone(), theme.description_title),
+            Line::styled(byline.join(" · "), theme.description),
+            Line::default(),
+        ];
+        text.lines.splice(0..0, header);
+
+        Reader { article, text, scroll: 0, full_text: FullText::Skipped }
+    }
+
+    /// Shows the full story fetched from the article's page in place of the feed's text, staying at the same scroll position
+    ///
+    /// Arguments
+    /// * story - The story, as HTML
+    /// * theme - The theme to style the text with
+    pub fn show_full_text(&mut self, story: String, theme: &Theme) {
+        let mut article = self.article.clone();
+        article.content = Some(story);
+        *self = Reader { scroll: self.scroll, full_text: FullText::Loaded, ..Reader::new(article, theme) };
+    }
+
+    /// The width and height of the text once the reader fills a terminal of the given size
+    ///
+    /// Arguments
+    /// * viewport - The width and height of the terminal
+    pub fn text_size(viewport: (u16, u16)) -> (u16, u16) {
+        (viewport.0.saturating_sub(CHROME_WIDTH), viewport.1.saturating_sub(CHROME_HEIGHT))
+    }
+
+    /// Scrolls by a number of rows, negative to scroll up, without running past either end of the text
+    ///
+    /// Arguments
+    /// * rows - How far to scroll
+    /// * viewport - The width and height of the terminal
+    pub fn scroll_by(&mut self, rows: i32, viewport: (u16, u16)) {
+        let (width, height) = Reader::text_size(viewport);
+        let max_scroll = self.text.height(width).saturating_sub(height as usize);
+        self.scroll = (self.scroll as i64 + rows as i64).clamp(0, max_scroll as i64) as usize;
+    }
+}
+
+/// Shows an article's text in place of the tabs
+pub struct ReaderView<'a> {
+    reader: &'a Reader,
+    theme: Theme,
+}
+
+impl ReaderView<'_> {
+    pub fn new<'a>(reader: &'a Reader, theme: &Theme) -> ReaderView<'a> {
+        ReaderView { reader, theme: theme.clone() }
+    }
+}
+
+impl Widget for ReaderView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .padding(Padding::horizontal(1))
+            .title(match &self.reader.full_text {
+                FullText::Loading => "Reader · fetching the full story…".to_string(),
+                FullText::Failed(error) => format!("Reader · full story unavailable: {}", error),
+                FullText::Skipped | FullText::Loaded => "Reader".to_string(),
+            })
+            .style(self.theme.content);
+        Paragraph::new(Text::from(self.reader.text.lines.clone()))
+            .wrap(Wrap { trim: false })
+            .scroll((self.reader.scroll as u16, 0))
+            .block(block)
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::newsroomcore::datasources::DataSources;
+
+    #[test]
+    fn test_reader_scroll(){
+        let article = NewsArticle {
+            authors: vec!["Jane Reporter".to_string()],
+            title: "Headline".to_string(),
+            summary: "Teaser".to_string(),
+            content: Some((1..=20).map(|i| format!("<p>Paragraph {}</p>", i)).collect()),
+            ..NewsArticle::test(&DataSources::new("source", "https://example.com"), "https://example.com/a")
+        };
+        let mut reader = Reader::new(article, &Theme::new());
+        assert_eq!(reader.text.lines[1].spans[0].content, "source · Jane Reporter");
+
+        // 3 header rows and 20 paragraphs with blank lines between, in a 10 row reader
+        let viewport = (80, 10 + CHROME_HEIGHT);
+        reader.scroll_by(-5, viewport);
+        assert_eq!(reader.scroll, 0);
+        reader.scroll_by(100, viewport);
+        assert_eq!(reader.scroll, 3 + 39 - 10);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{app::App, newsroomcore::{newsarticle::NewsArticle, readarticles::ReadArticles}, richtext::RichText, settings::Theme, tabs::NewsTab};
+use tui::{
+    prelude::*,
+    widgets::*,
+};
+
+/// Lists the articles the user starred, navigated and opened just like the news list
+pub struct SavedTab<'a>{
+    articles: &'a [NewsArticle],
+    list_state: ListState,
+    theme: Theme,
+    read: &'a ReadArticles,
+}
+
+impl SavedTab<'_>{
+    pub fn new(context: &App) -> SavedTab<'_> {
+        let mut list_state = ListState::default();
+        list_state.select(context.saved_row);
+        SavedTab {
+            articles: context.saved.articles(),
+            list_state,
+            theme: context.settings.theme.clone(),
+            read: &context.read,
+        }
+    }
+
+    pub fn render_saved_list(&mut self, area: Rect, buf: &mut Buffer) {
+        let items: Vec<ListItem> = self.articles
+            .iter()
+            .map(|x| {
+                let text = Text::from(NewsTab::wrap_text(format!("{}: {}", x.source.name, x.title), area.width as usize));
+                match self.read.is_read(&x.id) {
+                    true => ListItem::new(text),
+                    false => ListItem::new(text).add_modifier(Modifier::BOLD),
+                }
+            })
+            .collect();
+        let list_widget = List::new(items)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .style(self.theme.content)
+            .block(Block::new().borders(Borders::ALL).border_type(BorderType::Rounded).title(format!(" Saved ({}) ", self.articles.len())));
+
+        tui::widgets::StatefulWidget::render(list_widget, area, buf, &mut self.list_state);
+    }
+}
+
+impl Widget for SavedTab<'_>{
+    fn render(mut self, area: Rect, buf: &mut Buffer) {
+        if self.articles.is_empty() {
+            Paragraph::new("Press 's' on an article to save it for later").alignment(Alignment::Center).render(area, buf);
+            return;
+        }
+
+        match self.list_state.selected().and_then(|index| self.articles.get(index)) {
+            Some(article) => {
+                // The user has selected a row, display an extended description below the list
+                let layout = Layout::new()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Percentage(80),
+                    Constraint::Percentage(20),
+                ])
+                .split(area);
+
+                let text = RichText::from_html(&article.summary, &self.theme);
+                Paragraph::new(text.lines).wrap(Wrap { trim: false }).style(self.theme.content).block(Block::new().borders(Borders::ALL).border_type(BorderType::Rounded).title("Description")).render(layout[1], buf);
+                self.render_saved_list(layout[0], buf);
+            },
+            None => self.render_saved_list(area, buf),
+        }
+    }
+}
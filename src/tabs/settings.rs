@@ -3,42 +3,137 @@ use tui::{
     widgets::{*},
 };
 
-use crate::settings::Settings;
+use crate::{app::App, settings::{Settings, Theme}};
+
+/// The themes being picked from in the settings tab, with the highlighted one previewed across the whole app
+#[derive(Debug, Clone)]
+pub struct ThemePicker {
+    pub names: Vec<String>,
+    pub selected: usize,
+    /// The theme in use before the picker was opened, put back if the picker is cancelled
+    pub original: Theme,
+}
+
+impl ThemePicker {
+    /// Starts picking with the current theme highlighted
+    ///
+    /// Arguments
+    /// * settings - The settings holding the current theme and the custom themes
+    pub fn new(settings: &Settings) -> ThemePicker {
+        let names = settings.theme_names();
+        let selected = names.iter().position(|name| *name == settings.theme.name).unwrap_or(0);
+        ThemePicker { names, selected, original: settings.theme.clone() }
+    }
+
+    /// Highlights the next theme, or the previous one, wrapping around at either end
+    ///
+    /// Arguments
+    /// * forward - Whether to move down the list rather than up
+    pub fn step(&mut self, forward: bool) {
+        let count = self.names.len().max(1);
+        self.selected = match forward {
+            true => (self.selected + 1) % count,
+            false => (self.selected + count - 1) % count,
+        };
+    }
+
+    /// The name of the highlighted theme
+    pub fn name(&self) -> &str {
+        self.names.get(self.selected).map_or("", |name| name.as_str())
+    }
+}
 
 pub struct SettingsTab{
-    settings: Settings
+    settings: Settings,
+    /// The outcome of the last thing done from the settings tab
+    message: Option<String>,
+    list_state: ListState,
+    /// The themes to list, and which is highlighted while picking one
+    themes: Vec<String>,
+    theme_state: ListState,
 }
 
 impl SettingsTab{
-    pub fn new(settings: Settings) -> SettingsTab{
-        SettingsTab { settings }
+    pub fn new(context: &App) -> SettingsTab{
+        let mut list_state = ListState::default();
+        list_state.select(context.settings_row);
+        let mut theme_state = ListState::default();
+        theme_state.select(context.theme_picker.as_ref().map(|picker| picker.selected));
+        SettingsTab {
+            settings: context.settings.clone(),
+            message: context.settings_message.clone(),
+            list_state,
+            themes: context.settings.theme_names(),
+            theme_state,
+        }
     }
 
     /// Render the sources stored in settings
-    fn render_sources (&self, area: Rect, buf: &mut Buffer) {
+    fn render_sources (&mut self, area: Rect, buf: &mut Buffer) {
         let items: Vec<ListItem> = self.settings.sources
         .iter()
-        .map(|x| ListItem::new(x.name.to_string()))
+        .map(|x| {
+            let mut spans = vec![Span::raw(format!("{} {}", if x.disabled { "○" } else { "●" }, x.name))];
+            if let Some(category) = &x.category {
+                spans.push(Span::styled(format!("  {}", category), self.settings.theme.description));
+            }
+            spans.push(Span::styled(format!("  {}", x.url), self.settings.theme.description));
+            match x.disabled {
+                true => ListItem::new(Line::from(spans)).add_modifier(Modifier::DIM),
+                false => ListItem::new(Line::from(spans)),
+            }
+        })
         .collect();
+        let enabled = self.settings.sources.iter().filter(|source| !source.disabled).count();
         let list_widget = List::new(items)
-        .style(self.settings.theme.content).block(Block::new().borders(Borders::ALL).border_type(BorderType::Rounded).title("Sources"));
-        tui::widgets::Widget::render(list_widget, area, buf);
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .style(self.settings.theme.content)
+        .block(Block::new().borders(Borders::ALL).border_type(BorderType::Rounded).title(format!("Sources ({} of {} enabled)", enabled, self.settings.sources.len())));
+        tui::widgets::StatefulWidget::render(list_widget, area, buf, &mut self.list_state);
     }
 
-    fn render_theme (&self, area: Rect, buf: &mut Buffer) {
-        let text = self.settings.theme.name.clone();
-        Paragraph::new(text).style(self.settings.theme.content).block(Block::new().borders(Borders::ALL).border_type(BorderType::Rounded).title("Active theme")).render(area, buf);
+    /// Render the themes to choose from, marking the one in use
+    fn render_theme (&mut self, area: Rect, buf: &mut Buffer) {
+        let items: Vec<ListItem> = self.themes
+            .iter()
+            .map(|name| match *name == self.settings.theme.name {
+                true => ListItem::new(format!("● {}", name)),
+                false => ListItem::new(format!("  {}", name)),
+            })
+            .collect();
+        let title = match self.theme_state.selected() {
+            Some(_) => "Theme (previewing)",
+            None => "Theme (t to change)",
+        };
+        let list_widget = List::new(items)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .style(self.settings.theme.content)
+        .block(Block::new().borders(Borders::ALL).border_type(BorderType::Rounded).title(title));
+        tui::widgets::StatefulWidget::render(list_widget, area, buf, &mut self.theme_state);
     }
 }
 
 impl Widget for SettingsTab{
     /// Render the settings tab widget
-    fn render(self, area: Rect, buf: &mut Buffer) {
+    fn render(mut self, area: Rect, buf: &mut Buffer) {
+        let area = match &self.message {
+            Some(message) => {
+                let split = Layout::new()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(1)])
+                    .split(area);
+                Paragraph::new(message.as_str()).style(self.settings.theme.content).render(split[1], buf);
+                split[0]
+            },
+            None => area,
+        };
+        // The theme list gets a row per theme, but never more than half the tab
+        let theme_rows = (self.themes.len() as u16 + 2).min(area.height / 2);
         let layout = Layout::new()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Percentage(80),
-            Constraint::Percentage(20),
+            Constraint::Min(0),
+            Constraint::Length(theme_rows),
         ])
         .split(area);
         self.render_sources(layout[0], buf);
@@ -0,0 +1,401 @@
+use std::collections::BTreeMap;
+use tui::style::{Style, Color, Modifier};
+use serde::{Deserialize, Serialize};
+
+/// The names of the themes that come with newsroom, in the order they are offered
+pub const BUILTIN_THEMES: [&str; 5] = ["default", "light", "solarized", "high-contrast", "monochrome"];
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: String,
+    pub root: Style,
+    pub content: Style,
+    pub app_title: Style,
+    pub tabs: Style,
+    pub tabs_selected: Style,
+    pub borders: Style,
+    pub description: Style,
+    pub description_title: Style,
+    pub error: Style,
+    pub success: Style,
+    /// Marks the parts of a headline matching the search
+    pub highlight: Style,
+    /// Links inside article text
+    pub link: Style,
+    /// Code and preformatted text inside article text
+    pub code: Style,
+    pub keybinding: KeyBinding,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyBinding {
+    pub key: Style,
+    pub description: Style,
+}
+
+/// The handful of colours a built in theme is made from
+struct Palette {
+    background: Color,
+    text: Color,
+    /// Titles and the selected tab
+    strong: Color,
+    /// Unselected tabs
+    muted: Color,
+    error: Color,
+    success: Color,
+    highlight: (Color, Color),
+    link: Color,
+    code: Color,
+    /// The foreground and background of keys in the bottom bar, their descriptions swap the two
+    key: (Color, Color),
+}
+
+impl Theme {
+    /// The default dark blue theme
+    pub fn new() -> Theme {
+        Theme::from_palette("default", Palette {
+            background: DARK_BLUE,
+            text: LIGHT_GRAY,
+            strong: WHITE,
+            muted: MID_GRAY,
+            error: LIGHT_RED,
+            success: LIGHT_GREEN,
+            highlight: (BLACK, LIGHT_YELLOW),
+            link: LIGHT_BLUE,
+            code: LIGHT_YELLOW,
+            key: (BLACK, DARK_GRAY),
+        })
+    }
+
+    /// Looks up a theme by name, first among the themes defined in newsroom.toml, then the built in ones
+    ///
+    /// Arguments
+    /// * name - The name of the theme
+    /// * custom - The themes defined in newsroom.toml
+    pub fn load(name: &str, custom: &BTreeMap<String, ThemeConfig>) -> Result<Theme, String> {
+        match (custom.get(name), Theme::builtin(name)) {
+            (Some(config), _) => config.to_theme(name, custom),
+            (None, Some(theme)) => Ok(theme),
+            (None, None) => Err(format!("there is no theme called \"{}\"", name)),
+        }
+    }
+
+    /// One of the themes that come with newsroom
+    ///
+    /// Arguments
+    /// * name - One of BUILTIN_THEMES
+    pub fn builtin(name: &str) -> Option<Theme> {
+        let theme = match name {
+            "default" => Theme::new(),
+            "light" => Theme::from_palette(name, Palette {
+                background: Color::Indexed(255),
+                text: Color::Indexed(236),
+                strong: Color::Indexed(232),
+                muted: Color::Indexed(244),
+                error: Color::Rgb(176, 32, 32),
+                success: Color::Rgb(32, 128, 48),
+                highlight: (Color::Indexed(232), Color::Rgb(255, 224, 96)),
+                link: Color::Rgb(32, 80, 192),
+                code: Color::Rgb(150, 90, 0),
+                key: (Color::Indexed(255), Color::Indexed(240)),
+            }),
+            "solarized" => Theme::from_palette(name, Palette {
+                background: Color::Rgb(0x00, 0x2b, 0x36),
+                text: Color::Rgb(0x83, 0x94, 0x96),
+                strong: Color::Rgb(0x93, 0xa1, 0xa1),
+                muted: Color::Rgb(0x58, 0x6e, 0x75),
+                error: Color::Rgb(0xdc, 0x32, 0x2f),
+                success: Color::Rgb(0x85, 0x99, 0x00),
+                highlight: (Color::Rgb(0x00, 0x2b, 0x36), Color::Rgb(0xb5, 0x89, 0x00)),
+                link: Color::Rgb(0x26, 0x8b, 0xd2),
+                code: Color::Rgb(0x2a, 0xa1, 0x98),
+                key: (Color::Rgb(0x00, 0x2b, 0x36), Color::Rgb(0x93, 0xa1, 0xa1)),
+            }),
+            "high-contrast" => Theme::from_palette(name, Palette {
+                background: Color::Black,
+                text: Color::White,
+                strong: Color::White,
+                muted: Color::Gray,
+                error: Color::LightRed,
+                success: Color::LightGreen,
+                highlight: (Color::Black, Color::Yellow),
+                link: Color::LightCyan,
+                code: Color::LightYellow,
+                key: (Color::Black, Color::White),
+            }),
+            // Leaves the terminal's own colours alone, telling things apart with modifiers instead
+            "monochrome" => {
+                let reset = Color::Reset;
+                let mut theme = Theme::from_palette(name, Palette {
+                    background: reset,
+                    text: reset,
+                    strong: reset,
+                    muted: reset,
+                    error: reset,
+                    success: reset,
+                    highlight: (reset, reset),
+                    link: reset,
+                    code: reset,
+                    key: (reset, reset),
+                });
+                theme.tabs = theme.tabs.add_modifier(Modifier::DIM);
+                theme.description = theme.description.add_modifier(Modifier::DIM);
+                theme.error = theme.error.add_modifier(Modifier::BOLD);
+                theme.highlight = theme.highlight.add_modifier(Modifier::REVERSED);
+                theme.code = theme.code.add_modifier(Modifier::ITALIC);
+                theme.keybinding.key = theme.keybinding.key.add_modifier(Modifier::REVERSED);
+                theme
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
+
+    fn from_palette(name: &str, palette: Palette) -> Theme {
+        Theme {
+            name: name.to_string(),
+            root: Style::new().bg(palette.background),
+            content: Style::new().bg(palette.background).fg(palette.text),
+            app_title: Style::new()
+                .fg(palette.strong)
+                .bg(palette.background)
+                .add_modifier(Modifier::BOLD),
+            tabs: Style::new().fg(palette.muted).bg(palette.background),
+            tabs_selected: Style::new()
+                .fg(palette.strong)
+                .bg(palette.background)
+                .add_modifier(Modifier::BOLD)
+                .add_modifier(Modifier::REVERSED),
+            borders: Style::new().fg(palette.text),
+            description: Style::new().fg(palette.text).bg(palette.background),
+            description_title: Style::new().fg(palette.text).add_modifier(Modifier::BOLD),
+            error: Style::new().fg(palette.error).bg(palette.background),
+            success: Style::new().fg(palette.success).bg(palette.background),
+            highlight: Style::new().fg(palette.highlight.0).bg(palette.highlight.1),
+            link: Style::new().fg(palette.link).add_modifier(Modifier::UNDERLINED),
+            code: Style::new().fg(palette.code),
+            keybinding: KeyBinding {
+                key: Style::new().fg(palette.key.0).bg(palette.key.1),
+                description: Style::new().fg(palette.key.1).bg(palette.key.0),
+            }
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::new()
+    }
+}
+
+/// A theme defined in newsroom.toml under [themes.<name>]. Styles which are left out come from the base theme
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// The built in or custom theme to start from, "default" if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<StyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<StyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_title: Option<StyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tabs: Option<StyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tabs_selected: Option<StyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub borders: Option<StyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<StyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_title: Option<StyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<StyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<StyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<StyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<StyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<StyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keybinding: Option<KeyBindingConfig>,
+}
+
+/// The bottom bar styles of a theme defined in newsroom.toml
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeyBindingConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<StyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<StyleConfig>,
+}
+
+/// A style in newsroom.toml, like `{ fg = "#839496", bg = 235, modifiers = ["bold"] }`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StyleConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fg: Option<ColorConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bg: Option<ColorConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<String>,
+}
+
+/// A colour as a name ("light-red"), hex ("#rrggbb") or an index into the 256 colour palette
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ColorConfig {
+    Index(u8),
+    Name(String),
+}
+
+impl ThemeConfig {
+    /// Builds the theme, on top of its base theme
+    ///
+    /// Arguments
+    /// * name - The name the theme was defined under
+    /// * custom - The themes defined in newsroom.toml, which the base theme may be one of
+    fn to_theme(&self, name: &str, custom: &BTreeMap<String, ThemeConfig>) -> Result<Theme, String> {
+        let base_name = self.base.as_deref().unwrap_or("default");
+        let mut theme = match base_name == name {
+            // A theme named after a built in one can start from the built in version of itself
+            true => Theme::builtin(name).ok_or_else(|| format!("theme \"{}\" can't be based on itself", name))?,
+            // Drop this theme while looking up the base, so themes based on each other can't loop forever
+            false => {
+                let mut others = custom.clone();
+                others.remove(name);
+                Theme::load(base_name, &others)?
+            },
+        };
+        theme.name = name.to_string();
+
+        let styles = [
+            ("root", &self.root, &mut theme.root),
+            ("content", &self.content, &mut theme.content),
+            ("app_title", &self.app_title, &mut theme.app_title),
+            ("tabs", &self.tabs, &mut theme.tabs),
+            ("tabs_selected", &self.tabs_selected, &mut theme.tabs_selected),
+            ("borders", &self.borders, &mut theme.borders),
+            ("description", &self.description, &mut theme.description),
+            ("description_title", &self.description_title, &mut theme.description_title),
+            ("error", &self.error, &mut theme.error),
+            ("success", &self.success, &mut theme.success),
+            ("highlight", &self.highlight, &mut theme.highlight),
+            ("link", &self.link, &mut theme.link),
+            ("code", &self.code, &mut theme.code),
+        ];
+        for (field, config, style) in styles {
+            if let Some(config) = config {
+                *style = config.to_style().map_err(|e| format!("theme \"{}\", {}: {}", name, field, e))?;
+            }
+        }
+        let keybinding = self.keybinding.clone().unwrap_or_default();
+        let styles = [
+            ("keybinding.key", &keybinding.key, &mut theme.keybinding.key),
+            ("keybinding.description", &keybinding.description, &mut theme.keybinding.description),
+        ];
+        for (field, config, style) in styles {
+            if let Some(config) = config {
+                *style = config.to_style().map_err(|e| format!("theme \"{}\", {}: {}", name, field, e))?;
+            }
+        }
+        Ok(theme)
+    }
+}
+
+impl StyleConfig {
+    fn to_style(&self) -> Result<Style, String> {
+        let mut style = Style::new();
+        if let Some(fg) = &self.fg {
+            style = style.fg(fg.to_color()?);
+        }
+        if let Some(bg) = &self.bg {
+            style = style.bg(bg.to_color()?);
+        }
+        for modifier in &self.modifiers {
+            style = style.add_modifier(match modifier.to_lowercase().replace(['-', ' '], "_").as_str() {
+                "bold" => Modifier::BOLD,
+                "dim" => Modifier::DIM,
+                "italic" => Modifier::ITALIC,
+                "underline" | "underlined" => Modifier::UNDERLINED,
+                "slow_blink" | "blink" => Modifier::SLOW_BLINK,
+                "rapid_blink" => Modifier::RAPID_BLINK,
+                "reversed" | "reverse" => Modifier::REVERSED,
+                "hidden" => Modifier::HIDDEN,
+                "crossed_out" | "strikethrough" => Modifier::CROSSED_OUT,
+                _ => return Err(format!("unknown modifier \"{}\"", modifier)),
+            });
+        }
+        Ok(style)
+    }
+}
+
+impl ColorConfig {
+    fn to_color(&self) -> Result<Color, String> {
+        match self {
+            ColorConfig::Index(index) => Ok(Color::Indexed(*index)),
+            ColorConfig::Name(name) => name.trim().parse().map_err(|_| format!("unknown colour \"{}\"", name)),
+        }
+    }
+}
+
+const DARK_BLUE: Color = Color::Rgb(16, 24, 48);
+const LIGHT_BLUE: Color = Color::Rgb(64, 96, 192);
+const LIGHT_YELLOW: Color = Color::Rgb(192, 192, 96);
+const LIGHT_GREEN: Color = Color::Rgb(64, 192, 96);
+const LIGHT_RED: Color = Color::Rgb(192, 96, 96);
+// const RED: Color = Color::Indexed(160);
+const BLACK: Color = Color::Indexed(232); // not really black, often #080808
+const DARK_GRAY: Color = Color::Indexed(238);
+const MID_GRAY: Color = Color::Indexed(244);
+const LIGHT_GRAY: Color = Color::Indexed(250);
+const WHITE: Color = Color::Indexed(255); // not really white, often #eeeeee
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_builtin_themes(){
+        for name in BUILTIN_THEMES {
+            assert_eq!(Theme::load(name, &BTreeMap::new()).unwrap().name, name);
+        }
+        assert!(Theme::load("neon", &BTreeMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_custom_theme(){
+        let themes: BTreeMap<String, ThemeConfig> = toml::from_str(r##"
+            [mine]
+            base = "light"
+            content = { fg = "#102030", bg = "light-blue" }
+            error = { fg = 160, modifiers = ["bold", "underline"] }
+            keybinding = { key = { fg = "black", bg = "yellow" } }
+
+            [loop]
+            base = "loop2"
+            [loop2]
+            base = "loop"
+
+            [bad]
+            tabs = { fg = "blurple" }
+        "##).unwrap();
+
+        let theme = Theme::load("mine", &themes).unwrap();
+        assert_eq!(theme.name, "mine");
+        assert_eq!(theme.content, Style::new().fg(Color::Rgb(0x10, 0x20, 0x30)).bg(Color::LightBlue));
+        assert_eq!(theme.error, Style::new().fg(Color::Indexed(160)).add_modifier(Modifier::BOLD | Modifier::UNDERLINED));
+        assert_eq!(theme.keybinding.key, Style::new().fg(Color::Black).bg(Color::Yellow));
+        // Everything else comes from the base
+        assert_eq!(theme.root, Theme::builtin("light").unwrap().root);
+
+        assert!(Theme::load("loop", &themes).is_err());
+        assert_eq!(Theme::load("bad", &themes).unwrap_err(), "theme \"bad\", tabs: unknown colour \"blurple\"");
+    }
+}